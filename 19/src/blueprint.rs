@@ -0,0 +1,360 @@
+use std::{iter::Peekable, str::CharIndices};
+
+pub type Cost = u16;
+pub type ResourceId = usize;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Recipe {
+    pub robot: ResourceId,
+    pub costs: Vec<(ResourceId, Cost)>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Blueprint {
+    pub id: u8,
+    pub resources: Vec<String>,
+    pub recipes: Vec<Recipe>,
+}
+
+impl Blueprint {
+    pub fn resource_id(&self, name: &str) -> Option<ResourceId> {
+        self.resources.iter().position(|resource| resource == name)
+    }
+
+    fn intern(&mut self, name: &str) -> ResourceId {
+        match self.resource_id(name) {
+            Some(id) => id,
+            None => {
+                self.resources.push(name.to_string());
+                self.resources.len() - 1
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseBlueprintError {
+    pub position: usize,
+    pub msg: String,
+}
+
+impl ParseBlueprintError {
+    fn new(position: usize, msg: &str) -> ParseBlueprintError {
+        ParseBlueprintError {
+            position,
+            msg: msg.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Token<'a> {
+    Word(&'a str),
+    Number(u32),
+    Colon,
+    Period,
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Lexer<'a> {
+        Lexer {
+            input,
+            chars: input.char_indices().peekable(),
+        }
+    }
+
+    fn take_while(&mut self, start: usize, predicate: fn(char) -> bool) -> &'a str {
+        let mut end = start;
+        while let Some((i, c)) = self.chars.peek() {
+            if !predicate(*c) {
+                break;
+            }
+            end = i + c.len_utf8();
+            self.chars.next();
+        }
+        &self.input[start..end]
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<(usize, Token<'a>), ParseBlueprintError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+
+        let (start, c) = *self.chars.peek()?;
+
+        let token = match c {
+            ':' => {
+                self.chars.next();
+                Token::Colon
+            }
+            '.' => {
+                self.chars.next();
+                Token::Period
+            }
+            c if c.is_ascii_digit() => {
+                let digits = self.take_while(start, |c| c.is_ascii_digit());
+                match digits.parse() {
+                    Ok(number) => Token::Number(number),
+                    Err(_) => {
+                        return Some(Err(ParseBlueprintError::new(start, "number too large")))
+                    }
+                }
+            }
+            c if c.is_alphabetic() => {
+                Token::Word(self.take_while(start, |c| c.is_alphanumeric() || c == '_' || c == '-'))
+            }
+            _ => {
+                self.chars.next();
+                return Some(Err(ParseBlueprintError::new(
+                    start,
+                    &format!("unexpected character '{}'", c),
+                )));
+            }
+        };
+
+        Some(Ok((start, token)))
+    }
+}
+
+// blueprints := blueprint*
+// blueprint  := "Blueprint" NUMBER ":" recipe+
+// recipe     := "Each" WORD "robot" "costs" cost ("and" cost)* "."
+// cost       := NUMBER WORD
+struct Parser<'a> {
+    tokens: Vec<(usize, Token<'a>)>,
+    position: usize,
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Result<Parser<'a>, ParseBlueprintError> {
+        Ok(Parser {
+            tokens: Lexer::new(input).collect::<Result<_, _>>()?,
+            position: 0,
+            end: input.len(),
+        })
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).map(|(_, token)| *token)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map(|(offset, _)| *offset)
+            .unwrap_or(self.end)
+    }
+
+    fn error(&self, expected: &str) -> ParseBlueprintError {
+        let found = match self.peek() {
+            Some(Token::Word(word)) => format!("'{}'", word),
+            Some(Token::Number(number)) => number.to_string(),
+            Some(Token::Colon) => "':'".to_string(),
+            Some(Token::Period) => "'.'".to_string(),
+            None => "end of input".to_string(),
+        };
+        ParseBlueprintError::new(
+            self.offset(),
+            &format!("expected {}, found {}", expected, found),
+        )
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseBlueprintError> {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            let description = match expected {
+                Token::Word(word) => format!("'{}'", word),
+                Token::Number(number) => number.to_string(),
+                Token::Colon => "':'".to_string(),
+                Token::Period => "'.'".to_string(),
+            };
+            Err(self.error(&description))
+        }
+    }
+
+    fn word(&mut self) -> Result<&'a str, ParseBlueprintError> {
+        match self.peek() {
+            Some(Token::Word(word)) => {
+                self.position += 1;
+                Ok(word)
+            }
+            _ => Err(self.error("a resource name")),
+        }
+    }
+
+    fn number(&mut self) -> Result<u32, ParseBlueprintError> {
+        match self.peek() {
+            Some(Token::Number(number)) => {
+                self.position += 1;
+                Ok(number)
+            }
+            _ => Err(self.error("a number")),
+        }
+    }
+
+    fn blueprints(&mut self) -> Result<Vec<Blueprint>, ParseBlueprintError> {
+        let mut blueprints = Vec::new();
+        while self.peek().is_some() {
+            blueprints.push(self.blueprint()?);
+        }
+        Ok(blueprints)
+    }
+
+    fn blueprint(&mut self) -> Result<Blueprint, ParseBlueprintError> {
+        self.expect(Token::Word("Blueprint"))?;
+        let offset = self.offset();
+        let id = self
+            .number()?
+            .try_into()
+            .map_err(|_| ParseBlueprintError::new(offset, "blueprint id too large"))?;
+        self.expect(Token::Colon)?;
+
+        let mut blueprint = Blueprint {
+            id,
+            resources: Vec::new(),
+            recipes: Vec::new(),
+        };
+
+        loop {
+            let recipe = self.recipe(&mut blueprint)?;
+            blueprint.recipes.push(recipe);
+
+            if self.peek() != Some(Token::Word("Each")) {
+                break;
+            }
+        }
+
+        Ok(blueprint)
+    }
+
+    fn recipe(&mut self, blueprint: &mut Blueprint) -> Result<Recipe, ParseBlueprintError> {
+        self.expect(Token::Word("Each"))?;
+        let robot = blueprint.intern(self.word()?);
+        self.expect(Token::Word("robot"))?;
+        self.expect(Token::Word("costs"))?;
+
+        let mut costs = vec![self.cost(blueprint)?];
+        while self.peek() == Some(Token::Word("and")) {
+            self.position += 1;
+            costs.push(self.cost(blueprint)?);
+        }
+        self.expect(Token::Period)?;
+
+        Ok(Recipe { robot, costs })
+    }
+
+    fn cost(
+        &mut self,
+        blueprint: &mut Blueprint,
+    ) -> Result<(ResourceId, Cost), ParseBlueprintError> {
+        let offset = self.offset();
+        let amount = self
+            .number()?
+            .try_into()
+            .map_err(|_| ParseBlueprintError::new(offset, "cost too large"))?;
+        let resource = blueprint.intern(self.word()?);
+        Ok((resource, amount))
+    }
+}
+
+pub fn parse_blueprints(input: &str) -> Result<Vec<Blueprint>, ParseBlueprintError> {
+    Parser::new(input)?.blueprints()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_blueprints, Blueprint, ParseBlueprintError, Recipe};
+
+    #[test]
+    fn test_parse_multi_line_blueprint() {
+        let input = "Blueprint 1:
+  Each ore robot costs 4 ore.
+  Each clay robot costs 2 ore.
+  Each obsidian robot costs 3 ore and 14 clay.
+  Each geode robot costs 2 ore and 7 obsidian.
+";
+        let expected = vec![Blueprint {
+            id: 1,
+            resources: vec![
+                "ore".to_string(),
+                "clay".to_string(),
+                "obsidian".to_string(),
+                "geode".to_string(),
+            ],
+            recipes: vec![
+                Recipe {
+                    robot: 0,
+                    costs: vec![(0, 4)],
+                },
+                Recipe {
+                    robot: 1,
+                    costs: vec![(0, 2)],
+                },
+                Recipe {
+                    robot: 2,
+                    costs: vec![(0, 3), (1, 14)],
+                },
+                Recipe {
+                    robot: 3,
+                    costs: vec![(0, 2), (2, 7)],
+                },
+            ],
+        }];
+        let actual = parse_blueprints(input);
+        assert_eq!(Ok(expected), actual);
+    }
+
+    #[test]
+    fn test_parse_new_resources_and_ingredients() {
+        let input = "Blueprint 7: Each ore robot costs 2 ore. Each crystal robot costs 1 ore and 2 clay and 3 obsidian.";
+        let blueprints = parse_blueprints(input).unwrap();
+        let blueprint = &blueprints[0];
+
+        assert_eq!(
+            vec!["ore", "crystal", "clay", "obsidian"],
+            blueprint.resources
+        );
+        assert_eq!(
+            Recipe {
+                robot: 1,
+                costs: vec![(0, 1), (2, 2), (3, 3)],
+            },
+            blueprint.recipes[1]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Err(ParseBlueprintError {
+                position: 34,
+                msg: "expected a number, found 'ore'".to_string(),
+            }),
+            parse_blueprints("Blueprint 1: Each ore robot costs ore.")
+        );
+        assert_eq!(
+            Err(ParseBlueprintError {
+                position: 39,
+                msg: "expected '.', found end of input".to_string(),
+            }),
+            parse_blueprints("Blueprint 1: Each ore robot costs 4 ore")
+        );
+        assert_eq!(
+            Err(ParseBlueprintError {
+                position: 11,
+                msg: "unexpected character ';'".to_string(),
+            }),
+            parse_blueprints("Blueprint 1; Each ore robot costs 4 ore.")
+        );
+    }
+}
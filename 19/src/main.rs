@@ -1,149 +1,152 @@
-use enum_map::{enum_map, Enum, EnumMap};
+mod blueprint;
+mod schedule;
 
 use std::{env, fs};
 
-type Cost = u16;
-type Count = u16;
-
-#[derive(Debug, PartialEq, Copy, Clone)]
-struct ObsidianRobotCost {
-    ore: Cost,
-    clay: Cost,
-}
+use blueprint::{parse_blueprints, Blueprint, Recipe, ResourceId};
+use schedule::{Build, Schedule};
 
-#[derive(Debug, PartialEq, Copy, Clone)]
-struct GeodeRobotCost {
-    ore: Cost,
-    obsidian: Cost,
-}
-
-#[derive(Debug, PartialEq, Copy, Clone)]
-struct Blueprint {
-    id: u8,
-    ore_robot_cost: Cost,
-    clay_robot_cost: Cost,
-    obsidian_robot_cost: ObsidianRobotCost,
-    geode_robot_cost: GeodeRobotCost,
-}
+type Count = u16;
 
-#[derive(Debug, PartialEq, Eq, Enum, Copy, Clone)]
-enum OreType {
-    Ore,
-    Clay,
-    Obsidian,
-    Geode,
-}
+const STARTING_ROBOT: &str = "ore";
+const TARGET_RESOURCE: &str = "geode";
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct State {
     minute: u8,
-    robots: EnumMap<OreType, Count>,
-    ores: EnumMap<OreType, Count>,
+    robots: Vec<Count>,
+    resources: Vec<Count>,
 }
 
 impl State {
-    fn iter(&self, blueprint: Blueprint) -> StateIter {
+    fn new(blueprint: &Blueprint) -> State {
+        let mut robots = vec![0; blueprint.resources.len()];
+
+        if let Some(starting_robot) = blueprint.resource_id(STARTING_ROBOT) {
+            robots[starting_robot] = 1;
+        }
+
+        State {
+            minute: 0,
+            robots,
+            resources: vec![0; blueprint.resources.len()],
+        }
+    }
+
+    fn collect(&mut self, minutes: u8) {
+        for (resource, robots) in self.resources.iter_mut().zip(self.robots.iter()) {
+            *resource += robots * minutes as Count;
+        }
+        self.minute += minutes;
+    }
+
+    fn minutes_until_affordable(&self, recipe: &Recipe) -> Option<u8> {
+        recipe
+            .costs
+            .iter()
+            .map(|(resource, cost)| {
+                let shortfall = cost.saturating_sub(self.resources[*resource]);
+                let rate = self.robots[*resource];
+
+                if shortfall == 0 {
+                    Some(0)
+                } else if rate == 0 {
+                    None
+                } else {
+                    u8::try_from(shortfall.div_ceil(rate)).ok()
+                }
+            })
+            .try_fold(0, |minutes, wait| wait.map(|wait| minutes.max(wait)))
+    }
+
+    fn iter<'a>(&'a self, search: &'a Search) -> StateIter<'a> {
         StateIter {
             state: self,
             i: 0,
+            search,
+        }
+    }
+}
+
+struct Search<'a> {
+    blueprint: &'a Blueprint,
+    target: ResourceId,
+    max_time: u8,
+    max_useful_robots: Vec<Count>,
+}
+
+impl<'a> Search<'a> {
+    fn new(blueprint: &'a Blueprint, target: ResourceId, max_time: u8) -> Search<'a> {
+        let mut max_useful_robots = vec![0; blueprint.resources.len()];
+
+        for recipe in blueprint.recipes.iter() {
+            for (resource, cost) in recipe.costs.iter() {
+                max_useful_robots[*resource] = max_useful_robots[*resource].max(*cost);
+            }
+        }
+
+        max_useful_robots[target] = Count::MAX;
+
+        Search {
             blueprint,
+            target,
+            max_time,
+            max_useful_robots,
         }
     }
 }
 
+// Yields the states reached by waiting until each recipe is affordable and then
+// building its robot, skipping robots that could no longer pay for themselves.
 struct StateIter<'a> {
     state: &'a State,
     i: usize,
-    blueprint: Blueprint,
+    search: &'a Search<'a>,
 }
 
 impl<'a> Iterator for StateIter<'a> {
-    type Item = State;
+    type Item = (Build, State);
 
     fn next(&mut self) -> Option<Self::Item> {
-        fn a(b: &[(OreType, Cost)], robot: OreType, mut next: State) -> Option<State> {
-            let is_affordable = b.iter().all(|(typ, cost)| next.ores[*typ] >= *cost);
+        let recipes = &self.search.blueprint.recipes;
 
-            if !is_affordable {
-                return None;
-            }
+        while self.i < recipes.len() {
+            let recipe_index = self.i;
+            let recipe = &recipes[recipe_index];
+            self.i += 1;
 
-            if is_affordable {
-                for (typ, cost) in b.iter() {
-                    next.ores[*typ] -= cost
-                }
+            if self.state.robots[recipe.robot] >= self.search.max_useful_robots[recipe.robot] {
+                continue;
             }
 
-            next = build_robots(next);
+            let wait = match self.state.minutes_until_affordable(recipe) {
+                Some(wait) => wait,
+                None => continue,
+            };
 
-            if is_affordable {
-                next.robots[robot] += 1;
+            if self.state.minute as u16 + wait as u16 + 1 >= self.search.max_time as u16 {
+                continue;
             }
 
-            Some(next)
-        }
+            let mut next = self.state.clone();
+            next.collect(wait);
 
-        fn build_robots(mut next: State) -> State {
-            next.ores[OreType::Ore] += next.robots[OreType::Ore];
-            next.ores[OreType::Clay] += next.robots[OreType::Clay];
-            next.ores[OreType::Obsidian] += next.robots[OreType::Obsidian];
-            next.ores[OreType::Geode] += next.robots[OreType::Geode];
-
-            next
-        }
-
-        let blueprint = self.blueprint;
-
-        let mut maybe_next_val = None;
-        while maybe_next_val.is_none() {
-            if self.i > 4 {
-                break;
+            for (resource, cost) in recipe.costs.iter() {
+                next.resources[*resource] -= cost;
             }
 
-            maybe_next_val = match self.i {
-                0 => Some(build_robots(*self.state)),
-                1 => a(
-                    &[(OreType::Ore, blueprint.ore_robot_cost)],
-                    OreType::Ore,
-                    *self.state,
-                ),
-                2 => a(
-                    &[(OreType::Ore, blueprint.clay_robot_cost)],
-                    OreType::Clay,
-                    *self.state,
-                ),
-                3 => a(
-                    &[
-                        (OreType::Clay, blueprint.obsidian_robot_cost.clay),
-                        (OreType::Ore, blueprint.obsidian_robot_cost.ore),
-                    ],
-                    OreType::Obsidian,
-                    *self.state,
-                ),
-                4 => a(
-                    &[
-                        (OreType::Ore, blueprint.geode_robot_cost.ore),
-                        (OreType::Obsidian, blueprint.geode_robot_cost.obsidian),
-                    ],
-                    OreType::Geode,
-                    *self.state,
-                ),
-                _ => None,
+            next.collect(1);
+            next.robots[recipe.robot] += 1;
+
+            let build = Build {
+                minute: next.minute,
+                recipe: recipe_index,
             };
 
-            if maybe_next_val.is_none() {
-                self.i += 1;
-            }
+            return Some((build, next));
         }
 
-        self.i += 1;
-
-        maybe_next_val = maybe_next_val.map(|mut a: State| {
-            a.minute += 1;
-            a
-        });
-
-        maybe_next_val
+        None
     }
 }
 
@@ -152,147 +155,121 @@ fn load_input() -> String {
     fs::read_to_string(args.get(1).unwrap()).expect("Should have been able to read the file")
 }
 
-fn parse_blueprint(input: &str) -> Blueprint {
-    let mut split = input.split(": ");
-    let id = split.next().unwrap()[10..].parse::<u8>().unwrap();
-    let description = split.next().unwrap();
-
-    let mut robot_requirements = description.split(". ");
-
-    let ore_robot_cost = robot_requirements
-        .next()
-        .map(|val| val[21..val.len() - 4].parse::<Cost>().unwrap())
-        .unwrap();
-
-    let clay_robot_cost = robot_requirements
-        .next()
-        .map(|val| val[22..val.len() - 4].parse::<Cost>().unwrap())
-        .unwrap();
-
-    let (obsidian_robot_cost_ore, obsidian_robot_cost_clay) = robot_requirements
-        .next()
-        .map(|val| {
-            let stripped = &val[26..&val.len() - 5];
-            let mut splitted = stripped.split(" ore and ");
-            (
-                splitted.next().unwrap().parse::<Cost>().unwrap(),
-                splitted.next().unwrap().parse::<Cost>().unwrap(),
-            )
-        })
-        .unwrap();
-    let obsidian_robot_cost = ObsidianRobotCost {
-        ore: obsidian_robot_cost_ore,
-        clay: obsidian_robot_cost_clay,
-    };
-
-    let (geode_robot_cost_ore, geode_robot_cost_obsidian) = robot_requirements
-        .next()
-        .map(|val| {
-            let stripped = &val[23..&val.len() - 10];
-            let mut splitted = stripped.split(" ore and ");
-            (
-                splitted.next().unwrap().parse::<Cost>().unwrap(),
-                splitted.next().unwrap().parse::<Cost>().unwrap(),
-            )
-        })
-        .unwrap();
-    let geode_robot_cost = GeodeRobotCost {
-        ore: geode_robot_cost_ore,
-        obsidian: geode_robot_cost_obsidian,
-    };
-
-    Blueprint {
-        id,
-        ore_robot_cost,
-        clay_robot_cost,
-        obsidian_robot_cost,
-        geode_robot_cost,
-    }
+fn load_output_format() -> Option<String> {
+    env::args().nth(2)
 }
 
-fn parse_blueprints(input: &str) -> Vec<Blueprint> {
-    input
-        .split('\n')
-        .filter(|a| a != &"")
-        .map(parse_blueprint)
-        .collect()
+fn idle_until_end(search: &Search, state: &State) -> Count {
+    let remaining_time = (search.max_time - state.minute) as Count;
+
+    state.resources[search.target] + state.robots[search.target] * remaining_time
 }
 
-fn overestimate_maximum(state: State, max_time: u8) -> Count {
-    let remaining_time = (max_time - state.minute) as u16;
+fn overestimate_maximum(search: &Search, state: &State) -> Count {
+    let remaining_time = (search.max_time - state.minute) as Count;
 
-    let guarenteed_geodes: Count = state.robots[OreType::Geode] * remaining_time;
-    let estimated_geodes = (remaining_time * (remaining_time - 1)) / 2;
-    let total_geodes = state.ores[OreType::Geode] + guarenteed_geodes + estimated_geodes;
-    total_geodes
+    let estimated = (remaining_time * remaining_time.saturating_sub(1)) / 2;
+    idle_until_end(search, state) + estimated
 }
 
-fn dfs(blueprint: &Blueprint, state: State, max_time: u8, current_max: Count) -> Count {
+fn dfs(
+    search: &Search,
+    state: &State,
+    builds: &mut Vec<Build>,
+    max: &mut Count,
+    best_builds: &mut Vec<Build>,
+) {
     log::debug!("{:?}", state);
 
-    if state.minute == max_time {
-        return state.ores[OreType::Geode];
+    let idle = idle_until_end(search, state);
+    if idle > *max {
+        *max = idle;
+        best_builds.clone_from(builds);
     }
 
-    let mut max = current_max;
-
-    for new_state in state.iter(*blueprint) {
-        if overestimate_maximum(state, max_time) < max {
-            continue;
-        }
+    if overestimate_maximum(search, state) <= *max {
+        return;
+    }
 
-        let maybe_new_max = dfs(blueprint, new_state, max_time, max);
+    for (build, new_state) in state.iter(search) {
+        builds.push(build);
+        dfs(search, &new_state, builds, max, best_builds);
+        builds.pop();
+    }
+}
 
-        if maybe_new_max > max {
-            max = maybe_new_max;
-        }
+fn maximise_resource<'a>(
+    blueprint: &'a Blueprint,
+    target: &str,
+    minutes: u8,
+) -> (Count, Schedule<'a>) {
+    let mut max = 0;
+    let mut best_builds = Vec::new();
+
+    if let Some(target) = blueprint.resource_id(target) {
+        let search = Search::new(blueprint, target, minutes);
+        dfs(
+            &search,
+            &State::new(blueprint),
+            &mut Vec::new(),
+            &mut max,
+            &mut best_builds,
+        );
     }
 
-    max
+    (max, Schedule::new(blueprint, &best_builds, minutes))
 }
 
-fn maximise_geodes(blueprint: &Blueprint, minutes: u8) -> Count {
-    let initial_state = State {
-        minute: 0,
-        robots: enum_map! {
-        OreType::Ore => 1,
-        OreType::Clay => 0,
-        OreType::Obsidian => 0,
-        OreType::Geode => 0,
-        },
-        ores: enum_map! {
-        OreType::Ore => 0,
-        OreType::Clay => 0,
-        OreType::Obsidian => 0,
-        OreType::Geode => 0,
-        },
-    };
-
-    dfs(blueprint, initial_state, minutes, 0)
+fn maximise_geodes(blueprint: &Blueprint, minutes: u8) -> (Count, Schedule<'_>) {
+    maximise_resource(blueprint, TARGET_RESOURCE, minutes)
 }
 
 fn get_quality_level_sum(blueprints: &[Blueprint], minutes: u8) -> Count {
     blueprints
         .iter()
-        .map(|blueprint| blueprint.id as Count * maximise_geodes(blueprint, minutes))
+        .map(|blueprint| blueprint.id as Count * maximise_geodes(blueprint, minutes).0)
         .sum()
 }
 
 fn get_geode_product(blueprints: &[Blueprint], minutes: u8) -> Count {
     blueprints
         .iter()
-        .map(|blueprint| maximise_geodes(blueprint, minutes))
+        .map(|blueprint| maximise_geodes(blueprint, minutes).0)
         .product()
 }
 
+fn print_schedules(blueprints: &[Blueprint], minutes: u8, format: &str) {
+    for blueprint in blueprints {
+        let (geodes, schedule) = maximise_geodes(blueprint, minutes);
+
+        match format {
+            "--schedule" => {
+                println!("Blueprint {}: {} geodes", blueprint.id, geodes);
+                println!();
+                println!("{}", schedule);
+            }
+            "--csv" => {
+                println!("# Blueprint {}", blueprint.id);
+                print!("{}", schedule.to_csv());
+            }
+            _ => panic!("Unknown output format {}", format),
+        }
+    }
+}
+
 fn main() {
     env_logger::init();
     let input = load_input();
-    let blueprints = parse_blueprints(&input);
+    let blueprints = parse_blueprints(&input).unwrap();
+
+    if let Some(format) = load_output_format() {
+        print_schedules(&blueprints, 24, &format);
+        return;
+    }
+
     let quality_level_sum = get_quality_level_sum(&blueprints, 24);
     println!("{}", quality_level_sum);
 
-    // Warning this takes 11 hours on input.
     let first_3: Vec<Blueprint> = blueprints.iter().take(3).cloned().collect();
     let geode_product = get_geode_product(&first_3, 32);
     println!("{}", geode_product);
@@ -301,35 +278,69 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use crate::{
-        get_quality_level_sum, maximise_geodes, parse_blueprints, Blueprint, GeodeRobotCost,
-        ObsidianRobotCost,
+        blueprint::{Blueprint, Recipe},
+        get_geode_product, get_quality_level_sum, maximise_geodes, maximise_resource,
+        parse_blueprints,
     };
 
     fn init() {
         let _ = env_logger::builder().is_test(true).try_init();
     }
 
+    fn resources() -> Vec<String> {
+        vec![
+            "ore".to_string(),
+            "clay".to_string(),
+            "obsidian".to_string(),
+            "geode".to_string(),
+        ]
+    }
+
     fn get_test_blueprints() -> Vec<Blueprint> {
         vec![
             Blueprint {
                 id: 1,
-                ore_robot_cost: 4,
-                clay_robot_cost: 2,
-                obsidian_robot_cost: ObsidianRobotCost { ore: 3, clay: 14 },
-                geode_robot_cost: GeodeRobotCost {
-                    ore: 2,
-                    obsidian: 7,
-                },
+                resources: resources(),
+                recipes: vec![
+                    Recipe {
+                        robot: 0,
+                        costs: vec![(0, 4)],
+                    },
+                    Recipe {
+                        robot: 1,
+                        costs: vec![(0, 2)],
+                    },
+                    Recipe {
+                        robot: 2,
+                        costs: vec![(0, 3), (1, 14)],
+                    },
+                    Recipe {
+                        robot: 3,
+                        costs: vec![(0, 2), (2, 7)],
+                    },
+                ],
             },
             Blueprint {
                 id: 2,
-                ore_robot_cost: 2,
-                clay_robot_cost: 3,
-                obsidian_robot_cost: ObsidianRobotCost { ore: 3, clay: 8 },
-                geode_robot_cost: GeodeRobotCost {
-                    ore: 3,
-                    obsidian: 12,
-                },
+                resources: resources(),
+                recipes: vec![
+                    Recipe {
+                        robot: 0,
+                        costs: vec![(0, 2)],
+                    },
+                    Recipe {
+                        robot: 1,
+                        costs: vec![(0, 3)],
+                    },
+                    Recipe {
+                        robot: 2,
+                        costs: vec![(0, 3), (1, 8)],
+                    },
+                    Recipe {
+                        robot: 3,
+                        costs: vec![(0, 3), (2, 12)],
+                    },
+                ],
             },
         ]
     }
@@ -339,7 +350,7 @@ mod tests {
         init();
         let input = include_str!("../test.txt");
         let expected = get_test_blueprints();
-        let actual = parse_blueprints(&input);
+        let actual = parse_blueprints(input).unwrap();
         assert_eq!(expected, actual);
     }
 
@@ -349,11 +360,11 @@ mod tests {
         let blueprints = get_test_blueprints();
 
         let expected_1 = 9;
-        let actual_1 = maximise_geodes(blueprints.get(0).unwrap(), 24);
+        let (actual_1, _) = maximise_geodes(blueprints.first().unwrap(), 24);
         assert_eq!(expected_1, actual_1);
 
         let expected_2 = 12;
-        let actual_2 = maximise_geodes(blueprints.get(1).unwrap(), 24);
+        let (actual_2, _) = maximise_geodes(blueprints.get(1).unwrap(), 24);
         assert_eq!(expected_2, actual_2);
     }
 
@@ -366,4 +377,30 @@ mod tests {
         let actual = get_quality_level_sum(&blueprints, 24);
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_get_geode_product() {
+        init();
+        let blueprints = get_test_blueprints();
+
+        let expected = 56 * 62;
+        let actual = get_geode_product(&blueprints, 32);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_maximise_custom_resources() {
+        init();
+        let blueprints = parse_blueprints(
+            "Blueprint 1: Each geode robot costs 1 ore.
+             Blueprint 2: Each ore robot costs 1 ore. Each geode robot costs 1 crystal.
+             Blueprint 3: Each crystal robot costs 2 ore. Each geode robot costs 1 ore and 1 crystal.",
+        )
+        .unwrap();
+
+        assert_eq!(3, maximise_geodes(&blueprints[0], 4).0);
+        assert_eq!(0, maximise_geodes(&blueprints[1], 24).0);
+        assert_eq!(0, maximise_resource(&blueprints[0], "diamond", 24).0);
+        assert_eq!(2, maximise_resource(&blueprints[2], "crystal", 5).0);
+    }
 }
@@ -0,0 +1,244 @@
+use std::fmt::{Display, Write};
+
+use crate::{blueprint::Blueprint, Count, State};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Build {
+    pub minute: u8,
+    pub recipe: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Step {
+    pub minute: u8,
+    pub recipe: Option<usize>,
+    pub resources_before: Vec<Count>,
+    pub resources_after: Vec<Count>,
+    pub robots: Vec<Count>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Schedule<'a> {
+    blueprint: &'a Blueprint,
+    steps: Vec<Step>,
+}
+
+impl<'a> Schedule<'a> {
+    pub fn new(blueprint: &'a Blueprint, builds: &[Build], minutes: u8) -> Schedule<'a> {
+        let mut state = State::new(blueprint);
+        let mut steps = Vec::new();
+
+        for minute in 1..=minutes {
+            let resources_before = state.resources.clone();
+            let recipe = builds
+                .iter()
+                .find(|build| build.minute == minute)
+                .map(|build| build.recipe);
+
+            if let Some(recipe) = recipe {
+                for (resource, cost) in blueprint.recipes[recipe].costs.iter() {
+                    state.resources[*resource] -= cost;
+                }
+            }
+
+            let robots = state.robots.clone();
+            state.collect(1);
+
+            if let Some(recipe) = recipe {
+                state.robots[blueprint.recipes[recipe].robot] += 1;
+            }
+
+            steps.push(Step {
+                minute,
+                recipe,
+                resources_before,
+                resources_after: state.resources.clone(),
+                robots,
+            });
+        }
+
+        Schedule { blueprint, steps }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let resources = &self.blueprint.resources;
+        let mut csv = String::from("minute,started");
+
+        for suffix in ["before", "after", "robots"] {
+            for resource in resources.iter() {
+                write!(csv, ",{}_{}", resource, suffix).unwrap();
+            }
+        }
+        csv.push('\n');
+
+        for step in self.steps.iter() {
+            let started = step
+                .recipe
+                .map(|recipe| resources[self.blueprint.recipes[recipe].robot].as_str())
+                .unwrap_or("");
+            write!(csv, "{},{}", step.minute, started).unwrap();
+
+            for counts in [&step.resources_before, &step.resources_after, &step.robots] {
+                for count in counts.iter() {
+                    write!(csv, ",{}", count).unwrap();
+                }
+            }
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
+
+fn article(word: &str) -> &'static str {
+    match word.chars().next() {
+        Some('a' | 'e' | 'i' | 'o' | 'u') => "an",
+        _ => "a",
+    }
+}
+
+impl<'a> Display for Schedule<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let resources = &self.blueprint.resources;
+
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            writeln!(f, "== Minute {} ==", step.minute)?;
+
+            if let Some(recipe) = step.recipe {
+                let recipe = &self.blueprint.recipes[recipe];
+                let costs: Vec<String> = recipe
+                    .costs
+                    .iter()
+                    .map(|(resource, cost)| format!("{} {}", cost, resources[*resource]))
+                    .collect();
+                let robot = &resources[recipe.robot];
+
+                writeln!(
+                    f,
+                    "Spend {} to start building {} {}-collecting robot.",
+                    costs.join(" and "),
+                    article(robot),
+                    robot
+                )?;
+            }
+
+            for (resource, robots) in step.robots.iter().enumerate() {
+                if *robots == 0 {
+                    continue;
+                }
+
+                let name = &resources[resource];
+                let (plural, verb) = if *robots == 1 {
+                    ("", "collects")
+                } else {
+                    ("s", "collect")
+                };
+
+                writeln!(
+                    f,
+                    "{} {}-collecting robot{} {} {} {}; you now have {} {}.",
+                    robots, name, plural, verb, robots, name, step.resources_after[resource], name
+                )?;
+            }
+
+            if let Some(recipe) = step.recipe {
+                let robot = self.blueprint.recipes[recipe].robot;
+
+                writeln!(
+                    f,
+                    "The new {}-collecting robot is ready; you now have {} of them.",
+                    resources[robot],
+                    step.robots[robot] + 1
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{blueprint::parse_blueprints, maximise_geodes};
+
+    use super::{Build, Schedule};
+
+    const TEST_BLUEPRINT: &str = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.";
+
+    fn get_example_builds() -> Vec<Build> {
+        [
+            (3, 1),
+            (5, 1),
+            (7, 1),
+            (11, 2),
+            (12, 1),
+            (15, 2),
+            (18, 3),
+            (21, 3),
+        ]
+        .iter()
+        .map(|(minute, recipe)| Build {
+            minute: *minute,
+            recipe: *recipe,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_display_schedule() {
+        let blueprints = parse_blueprints(TEST_BLUEPRINT).unwrap();
+        let schedule = Schedule::new(&blueprints[0], &get_example_builds(), 24);
+        let narrative = schedule.to_string();
+
+        let expected_start = "== Minute 1 ==
+1 ore-collecting robot collects 1 ore; you now have 1 ore.
+
+== Minute 2 ==
+1 ore-collecting robot collects 1 ore; you now have 2 ore.
+
+== Minute 3 ==
+Spend 2 ore to start building a clay-collecting robot.
+1 ore-collecting robot collects 1 ore; you now have 1 ore.
+The new clay-collecting robot is ready; you now have 1 of them.
+";
+        assert!(narrative.starts_with(expected_start));
+
+        let expected_end = "== Minute 24 ==
+1 ore-collecting robot collects 1 ore; you now have 6 ore.
+4 clay-collecting robots collect 4 clay; you now have 41 clay.
+2 obsidian-collecting robots collect 2 obsidian; you now have 8 obsidian.
+2 geode-collecting robots collect 2 geode; you now have 9 geode.
+";
+        assert!(narrative.ends_with(expected_end));
+    }
+
+    #[test]
+    fn test_schedule_to_csv() {
+        let blueprints = parse_blueprints(TEST_BLUEPRINT).unwrap();
+        let schedule = Schedule::new(&blueprints[0], &get_example_builds(), 24);
+        let csv = schedule.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(25, lines.len());
+        assert_eq!(
+            "minute,started,ore_before,clay_before,obsidian_before,geode_before,ore_after,clay_after,obsidian_after,geode_after,ore_robots,clay_robots,obsidian_robots,geode_robots",
+            lines[0]
+        );
+        assert_eq!("3,clay,2,0,0,0,1,0,0,0,1,0,0,0", lines[3]);
+        assert_eq!("24,,5,37,6,7,6,41,8,9,1,4,2,2", lines[24]);
+    }
+
+    #[test]
+    fn test_maximise_geodes_schedule() {
+        let blueprints = parse_blueprints(TEST_BLUEPRINT).unwrap();
+        let (geodes, schedule) = maximise_geodes(&blueprints[0], 24);
+        let last = schedule.steps.last().unwrap();
+
+        assert_eq!(24, schedule.steps.len());
+        assert_eq!(geodes, last.resources_after[3]);
+    }
+}
@@ -1,4 +1,8 @@
-use std::{env, fs};
+mod treap;
+
+use std::{env, fmt::Display, fs, process};
+
+use treap::ImplicitTreap;
 
 fn load_input() -> String {
     let args: Vec<String> = env::args().collect();
@@ -8,28 +12,29 @@ fn load_input() -> String {
 fn parse_input(input: &str) -> Vec<i64> {
     input
         .split('\n')
+        .filter(|val| val != &"")
         .map(|val| val.parse::<i64>().unwrap())
         .collect()
 }
 
-fn mix(coordinates: &[i64], rounds: u8) -> i64 {
-    let len = coordinates.len() as i64;
-    let mut positions: Vec<i64> = (0..len).collect();
-
-    for _ in 0..rounds {
-        for i in 0..coordinates.len() {
-            let old_position = positions[i];
-            let mut new_position = old_position + coordinates[i];
+// Returns the original indices of the coordinates in their mixed order.
+fn mix_indices(coordinates: &[i64], rounds: u8) -> Vec<usize> {
+    let mut treap = ImplicitTreap::new();
+    let ids: Vec<usize> = (0..coordinates.len()).map(|i| treap.push(i)).collect();
 
-            log::trace!("Pre-cycle new position: {}", new_position);
+    if coordinates.len() < 2 {
+        return ids;
+    }
 
-            if new_position <= 0 {
-                new_position = (new_position).rem_euclid(len - 1);
-            }
+    let cycle = coordinates.len() as i64 - 1;
 
-            if new_position >= len {
-                new_position = (new_position).rem_euclid(len - 1);
-            }
+    for _ in 0..rounds {
+        for (id, coordinate) in ids.iter().zip(coordinates.iter()) {
+            let old_position = treap.position(*id);
+            let removed = treap.remove(old_position);
+            // Reduced first, as a coordinate near the end of i64 would overflow the sum.
+            let new_position =
+                (old_position as i64 + coordinate.rem_euclid(cycle)).rem_euclid(cycle);
 
             log::trace!(
                 "Old Position: {}. New Position: {}",
@@ -37,60 +42,345 @@ fn mix(coordinates: &[i64], rounds: u8) -> i64 {
                 new_position
             );
 
-            let is_forward = new_position > old_position;
+            treap.insert(new_position as usize, removed);
+        }
+    }
 
-            for j in 0..positions.len() {
-                if j == i {
-                    continue;
-                }
-                // log::trace!("Current Position: {}", positions[j]);
-                if is_forward {
-                    if positions[j] >= old_position && positions[j] <= new_position {
-                        positions[j] -= 1;
-                    }
-                } else {
-                    if positions[j] >= new_position && positions[j] <= old_position {
-                        positions[j] += 1;
-                    }
-                }
+    treap.iter().map(|id| *treap.value(id)).collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DecryptError {
+    MissingAnchor(i64),
+    DuplicateAnchor(i64, Vec<usize>),
+    Overflow(i64),
+}
+
+impl Display for DecryptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecryptError::MissingAnchor(anchor) => {
+                write!(
+                    f,
+                    "no coordinate is {}, so there is nothing to count from",
+                    anchor
+                )
             }
+            DecryptError::DuplicateAnchor(anchor, indices) => {
+                let indices: Vec<String> = indices.iter().map(|i| i.to_string()).collect();
+                write!(
+                    f,
+                    "coordinate {} appears at indices {}, so the start is ambiguous",
+                    anchor,
+                    indices.join(", ")
+                )
+            }
+            DecryptError::Overflow(coordinate) => {
+                write!(
+                    f,
+                    "coordinate {} overflows when multiplied by the key",
+                    coordinate
+                )
+            }
+        }
+    }
+}
 
-            positions[i] = new_position;
+#[derive(Debug, PartialEq, Eq)]
+struct Decryption {
+    mixed: Vec<i64>,
+    anchor_index: usize,
+    coordinates: Vec<i64>,
+}
+
+impl Decryption {
+    fn sum(&self) -> i64 {
+        self.coordinates.iter().sum()
+    }
+}
+
+// The anchor is looked up among the coordinates as they were read, before
+// the key is applied, so the default anchor of 0 still works with any key.
+#[derive(Debug, Clone)]
+struct Decryptor {
+    key: i64,
+    rounds: u8,
+    anchor: i64,
+    offsets: Vec<usize>,
+}
 
-            log::debug!("{:?}", positions);
+impl Decryptor {
+    fn new() -> Decryptor {
+        Decryptor {
+            key: 1,
+            rounds: 1,
+            anchor: 0,
+            offsets: vec![1000, 2000, 3000],
         }
     }
 
-    let zeroth_position = coordinates.iter().position(|a| *a == 0).unwrap();
+    fn key(mut self, key: i64) -> Decryptor {
+        self.key = key;
+        self
+    }
+
+    fn rounds(mut self, rounds: u8) -> Decryptor {
+        self.rounds = rounds;
+        self
+    }
 
-    [1000, 2000, 3000]
-        .iter()
-        .map(|coordinate_position| (positions[zeroth_position] + coordinate_position) % len)
-        .map(|position| positions.iter().position(|a| *a == position).unwrap())
-        .map(|position| coordinates[position])
-        .map(|p| {
-            log::debug!("{}", p);
-            p
+    fn anchor(mut self, anchor: i64) -> Decryptor {
+        self.anchor = anchor;
+        self
+    }
+
+    fn offsets(mut self, offsets: &[usize]) -> Decryptor {
+        self.offsets = offsets.to_vec();
+        self
+    }
+
+    fn decrypt(&self, coordinates: &[i64]) -> Result<Decryption, DecryptError> {
+        let decrypted = coordinates
+            .iter()
+            .map(|val| {
+                val.checked_mul(self.key)
+                    .ok_or(DecryptError::Overflow(*val))
+            })
+            .collect::<Result<Vec<i64>, DecryptError>>()?;
+
+        let anchors: Vec<usize> = coordinates
+            .iter()
+            .enumerate()
+            .filter(|(_, val)| **val == self.anchor)
+            .map(|(i, _)| i)
+            .collect();
+
+        match anchors.len() {
+            0 => return Err(DecryptError::MissingAnchor(self.anchor)),
+            1 => {}
+            _ => return Err(DecryptError::DuplicateAnchor(self.anchor, anchors)),
+        }
+
+        let order = mix_indices(&decrypted, self.rounds);
+        let anchor_index = order.iter().position(|i| *i == anchors[0]).unwrap();
+        let mixed: Vec<i64> = order.into_iter().map(|i| decrypted[i]).collect();
+
+        let coordinates = self
+            .offsets
+            .iter()
+            .map(|offset| mixed[(anchor_index + offset) % mixed.len()])
+            .inspect(|p| log::debug!("{}", p))
+            .collect();
+
+        Ok(Decryption {
+            mixed,
+            anchor_index,
+            coordinates,
         })
-        .sum()
+    }
+}
+
+fn exit_with(error: DecryptError) -> Decryption {
+    eprintln!("Cannot decrypt: {}", error);
+    process::exit(1);
 }
 
 fn main() {
     env_logger::init();
     let input = load_input();
     let coordinates = parse_input(&input);
-    let mixed = mix(&coordinates, 1);
-    println!("{}", mixed);
+    let decryptor = Decryptor::new().anchor(0).offsets(&[1000, 2000, 3000]);
+
+    let decryption = decryptor
+        .clone()
+        .decrypt(&coordinates)
+        .unwrap_or_else(exit_with);
+    println!("{}", decryption.sum());
 
-    let decryption_key = 811589153;
-    let part_2_coordinates: Vec<i64> = coordinates.iter().map(|val| val * decryption_key).collect();
-    let part_2_mixed = mix(&part_2_coordinates, 10);
-    println!("{}", part_2_mixed);
+    let part_2_decryption = decryptor
+        .key(811589153)
+        .rounds(10)
+        .decrypt(&coordinates)
+        .unwrap_or_else(exit_with);
+    println!("{}", part_2_decryption.sum());
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::{mix_indices, parse_input, treap::XorShift, DecryptError, Decryptor};
+
     fn init() {
         let _ = env_logger::builder().is_test(true).try_init();
     }
+
+    // The original O(n²) mixing, kept as a reference for the treap-backed one.
+    fn naive_mix(coordinates: &[i64], rounds: u8) -> Vec<usize> {
+        let len = coordinates.len() as i64;
+        let mut positions: Vec<i64> = (0..len).collect();
+
+        for _ in 0..rounds {
+            for i in 0..coordinates.len() {
+                let old_position = positions[i];
+                let mut new_position = old_position + coordinates[i];
+
+                if new_position <= 0 {
+                    new_position = (new_position).rem_euclid(len - 1);
+                }
+
+                if new_position >= len {
+                    new_position = (new_position).rem_euclid(len - 1);
+                }
+
+                let is_forward = new_position > old_position;
+
+                for (j, position) in positions.iter_mut().enumerate() {
+                    if j == i {
+                        continue;
+                    }
+
+                    if is_forward {
+                        if *position >= old_position && *position <= new_position {
+                            *position -= 1;
+                        }
+                    } else if *position >= new_position && *position <= old_position {
+                        *position += 1;
+                    }
+                }
+
+                positions[i] = new_position;
+            }
+        }
+
+        let mut order: Vec<usize> = (0..coordinates.len()).collect();
+        order.sort_by_key(|i| positions[*i]);
+        order
+    }
+
+    // The mixed list is circular, so compare orders starting from the first number.
+    fn normalise(order: Vec<usize>) -> Vec<usize> {
+        let start = order.iter().position(|i| *i == 0).unwrap();
+        order[start..]
+            .iter()
+            .chain(order[..start].iter())
+            .cloned()
+            .collect()
+    }
+
+    fn range(rng: &mut XorShift, min: i64, max: i64) -> i64 {
+        min + (rng.next() % (max - min + 1) as u64) as i64
+    }
+
+    #[test]
+    fn test_mix_sequence() {
+        init();
+        let coordinates = parse_input(include_str!("../test.txt"));
+
+        let decryption = Decryptor::new().decrypt(&coordinates).unwrap();
+        let (mixed, zero) = (decryption.mixed, decryption.anchor_index);
+        let rotated: Vec<i64> = mixed[zero..]
+            .iter()
+            .chain(mixed[..zero].iter())
+            .cloned()
+            .collect();
+        assert_eq!(vec![0, 3, -2, 1, 2, -3, 4], rotated);
+    }
+
+    #[test]
+    fn test_decrypt() {
+        init();
+        let coordinates = parse_input(include_str!("../test.txt"));
+
+        let decryption = Decryptor::new().decrypt(&coordinates).unwrap();
+        assert_eq!(vec![4, -3, 2], decryption.coordinates);
+        assert_eq!(0, decryption.mixed[decryption.anchor_index]);
+        assert_eq!(3, decryption.sum());
+
+        let decryption = Decryptor::new()
+            .key(811589153)
+            .rounds(10)
+            .decrypt(&coordinates)
+            .unwrap();
+        assert_eq!(
+            vec![811589153, 2434767459, -1623178306],
+            decryption.coordinates
+        );
+        assert_eq!(1623178306, decryption.sum());
+    }
+
+    #[test]
+    fn test_decrypt_with_custom_anchor() {
+        init();
+        let coordinates = parse_input(include_str!("../test.txt"));
+
+        let decryption = Decryptor::new()
+            .anchor(3)
+            .offsets(&[0, 1, 8])
+            .decrypt(&coordinates)
+            .unwrap();
+        assert_eq!(vec![3, -2, -2], decryption.coordinates);
+    }
+
+    #[test]
+    fn test_decrypt_errors() {
+        init();
+
+        assert_eq!(
+            Err(DecryptError::MissingAnchor(0)),
+            Decryptor::new().decrypt(&[1, 2, 3])
+        );
+        assert_eq!(
+            Err(DecryptError::DuplicateAnchor(0, vec![0, 2])),
+            Decryptor::new().decrypt(&[0, 1, 0])
+        );
+        assert_eq!(
+            Err(DecryptError::Overflow(i64::MAX)),
+            Decryptor::new().key(2).decrypt(&[0, i64::MAX])
+        );
+        assert_eq!(
+            "coordinate 0 appears at indices 0, 2, so the start is ambiguous",
+            DecryptError::DuplicateAnchor(0, vec![0, 2]).to_string()
+        );
+    }
+
+    #[test]
+    fn test_mix_extreme_coordinates() {
+        init();
+        let coordinates = vec![0, i64::MAX, -4, i64::MAX - 1, 3, i64::MIN];
+        let cycle = coordinates.len() as i64 - 1;
+        let reduced: Vec<i64> = coordinates.iter().map(|c| c.rem_euclid(cycle)).collect();
+
+        assert_eq!(
+            normalise(naive_mix(&reduced, 3)),
+            normalise(mix_indices(&coordinates, 3))
+        );
+
+        let decryption = Decryptor::new()
+            .key(i64::MAX / 3)
+            .decrypt(&[0, 3, -3, 1])
+            .unwrap();
+        assert_eq!(0, decryption.mixed[decryption.anchor_index]);
+    }
+
+    #[test]
+    fn test_mix_matches_naive_mix() {
+        init();
+        let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..200 {
+            let len = range(&mut rng, 2, 40) as usize;
+            let magnitude = [3, 100, 811589153 * 10][range(&mut rng, 0, 2) as usize];
+            let coordinates: Vec<i64> = (0..len)
+                .map(|_| range(&mut rng, -magnitude, magnitude))
+                .collect();
+            let rounds = range(&mut rng, 1, 10) as u8;
+
+            assert_eq!(
+                normalise(naive_mix(&coordinates, rounds)),
+                normalise(mix_indices(&coordinates, rounds)),
+                "{:?} mixed {} times",
+                coordinates,
+                rounds
+            );
+        }
+    }
 }
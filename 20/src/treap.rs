@@ -0,0 +1,205 @@
+pub type NodeId = usize;
+
+// Plenty random enough to keep the treap balanced, and the same every run.
+#[derive(Debug, Clone)]
+pub struct XorShift(pub u64);
+
+impl XorShift {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    value: T,
+    priority: u64,
+    size: usize,
+    left: Option<NodeId>,
+    right: Option<NodeId>,
+    parent: Option<NodeId>,
+}
+
+// A treap keyed implicitly by in-order position. Nodes live in an arena and keep
+// their id while being moved around, so the position of a node can be recovered by
+// walking up its parent pointers.
+#[derive(Debug, Clone)]
+pub struct ImplicitTreap<T> {
+    nodes: Vec<Node<T>>,
+    root: Option<NodeId>,
+    priorities: XorShift,
+}
+
+impl<T> ImplicitTreap<T> {
+    pub fn new() -> ImplicitTreap<T> {
+        ImplicitTreap {
+            nodes: Vec::new(),
+            root: None,
+            priorities: XorShift(0x2545_f491_4f6c_dd1d),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.size(self.root)
+    }
+
+    pub fn push(&mut self, value: T) -> NodeId {
+        let id = self.nodes.len();
+        let priority = self.priorities.next();
+
+        self.nodes.push(Node {
+            value,
+            priority,
+            size: 1,
+            left: None,
+            right: None,
+            parent: None,
+        });
+
+        let root = self.merge(self.root, Some(id));
+        self.set_root(root);
+        id
+    }
+
+    pub fn value(&self, id: NodeId) -> &T {
+        &self.nodes[id].value
+    }
+
+    pub fn position(&self, id: NodeId) -> usize {
+        let mut index = self.size(self.nodes[id].left);
+        let mut current = id;
+
+        while let Some(parent) = self.nodes[current].parent {
+            if self.nodes[parent].right == Some(current) {
+                index += self.size(self.nodes[parent].left) + 1;
+            }
+            current = parent;
+        }
+
+        index
+    }
+
+    pub fn remove(&mut self, index: usize) -> NodeId {
+        assert!(index < self.len(), "Index {} out of bounds", index);
+
+        let (before, rest) = self.split(self.root, index);
+        let (removed, after) = self.split(rest, 1);
+        let root = self.merge(before, after);
+        self.set_root(root);
+
+        let id = removed.unwrap();
+        self.nodes[id].parent = None;
+        id
+    }
+
+    pub fn insert(&mut self, index: usize, id: NodeId) {
+        assert!(index <= self.len(), "Index {} out of bounds", index);
+
+        let (before, after) = self.split(self.root, index);
+        let root = self.merge(before, Some(id));
+        let root = self.merge(root, after);
+        self.set_root(root);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = NodeId> + '_ {
+        let mut stack = Vec::new();
+        let mut current = self.root;
+
+        std::iter::from_fn(move || {
+            while let Some(id) = current {
+                stack.push(id);
+                current = self.nodes[id].left;
+            }
+
+            let id = stack.pop()?;
+            current = self.nodes[id].right;
+            Some(id)
+        })
+    }
+
+    fn size(&self, node: Option<NodeId>) -> usize {
+        node.map(|id| self.nodes[id].size).unwrap_or(0)
+    }
+
+    fn set_root(&mut self, root: Option<NodeId>) {
+        if let Some(id) = root {
+            self.nodes[id].parent = None;
+        }
+        self.root = root;
+    }
+
+    fn update(&mut self, id: NodeId) {
+        let Node { left, right, .. } = self.nodes[id];
+
+        self.nodes[id].size = 1 + self.size(left) + self.size(right);
+
+        for child in [left, right].into_iter().flatten() {
+            self.nodes[child].parent = Some(id);
+        }
+    }
+
+    fn split(&mut self, node: Option<NodeId>, count: usize) -> (Option<NodeId>, Option<NodeId>) {
+        let id = match node {
+            Some(id) => id,
+            None => return (None, None),
+        };
+
+        let left_size = self.size(self.nodes[id].left);
+
+        if count <= left_size {
+            let (left, right) = self.split(self.nodes[id].left, count);
+            self.nodes[id].left = right;
+            self.update(id);
+            (left, Some(id))
+        } else {
+            let (left, right) = self.split(self.nodes[id].right, count - left_size - 1);
+            self.nodes[id].right = left;
+            self.update(id);
+            (Some(id), right)
+        }
+    }
+
+    fn merge(&mut self, left: Option<NodeId>, right: Option<NodeId>) -> Option<NodeId> {
+        match (left, right) {
+            (None, right) => right,
+            (left, None) => left,
+            (Some(l), Some(r)) => {
+                if self.nodes[l].priority > self.nodes[r].priority {
+                    self.nodes[l].right = self.merge(self.nodes[l].right, Some(r));
+                    self.update(l);
+                    Some(l)
+                } else {
+                    self.nodes[r].left = self.merge(Some(l), self.nodes[r].left);
+                    self.update(r);
+                    Some(r)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImplicitTreap;
+
+    #[test]
+    fn test_remove_and_insert() {
+        let mut treap = ImplicitTreap::new();
+        let ids: Vec<usize> = "abcdef".chars().map(|c| treap.push(c)).collect();
+
+        let removed = treap.remove(1);
+        assert_eq!(ids[1], removed);
+        assert_eq!(5, treap.len());
+
+        treap.insert(4, removed);
+        let actual: String = treap.iter().map(|id| *treap.value(id)).collect();
+        assert_eq!("acdebf", actual);
+
+        for (index, id) in treap.iter().enumerate() {
+            assert_eq!(index, treap.position(id));
+        }
+    }
+}
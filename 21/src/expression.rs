@@ -0,0 +1,308 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{rational::Rational, MonkeyId, Operation, Something, Value};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expression {
+    Constant(Rational),
+    Unknown(MonkeyId),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SolveError {
+    MissingMonkey(MonkeyId),
+    Cycle(MonkeyId),
+    NotAnEquation(MonkeyId),
+    DivisionByZero,
+    Overflow,
+    NonLinear,
+    NoSolution,
+    InfiniteSolutions,
+    NoIntegerSolution(Rational),
+}
+
+// The unknown's yell is a * unknown + b.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Linear {
+    coefficient: Rational,
+    constant: Rational,
+}
+
+fn split_operation(operation: &Operation) -> (Operator, &MonkeyId, &MonkeyId) {
+    match operation {
+        Operation::Add(a, b) => (Operator::Add, a, b),
+        Operation::Subtract(a, b) => (Operator::Subtract, a, b),
+        Operation::Multiply(a, b) => (Operator::Multiply, a, b),
+        Operation::Divide(a, b) => (Operator::Divide, a, b),
+    }
+}
+
+fn apply(operator: Operator, a: Rational, b: Rational) -> Result<Rational, SolveError> {
+    match operator {
+        Operator::Add => a.checked_add(b).ok_or(SolveError::Overflow),
+        Operator::Subtract => a.checked_sub(b).ok_or(SolveError::Overflow),
+        Operator::Multiply => a.checked_mul(b).ok_or(SolveError::Overflow),
+        Operator::Divide if b.is_zero() => Err(SolveError::DivisionByZero),
+        Operator::Divide => a.checked_div(b).ok_or(SolveError::Overflow),
+    }
+}
+
+fn build<'a>(
+    jobs: &'a HashMap<MonkeyId, Something>,
+    monkey_id: &'a str,
+    unknown: &str,
+    visiting: &mut HashSet<&'a str>,
+) -> Result<Expression, SolveError> {
+    if monkey_id == unknown {
+        return Ok(Expression::Unknown(monkey_id.to_string()));
+    }
+
+    if !visiting.insert(monkey_id) {
+        return Err(SolveError::Cycle(monkey_id.to_string()));
+    }
+
+    let expression = match jobs.get(monkey_id) {
+        None => return Err(SolveError::MissingMonkey(monkey_id.to_string())),
+        Some(Something::Number(value)) => Expression::Constant(Rational::integer(*value as i128)),
+        Some(Something::MathsOperation(operation)) => {
+            let (operator, a, b) = split_operation(operation);
+            Expression::Binary(
+                operator,
+                Box::new(build(jobs, a, unknown, visiting)?),
+                Box::new(build(jobs, b, unknown, visiting)?),
+            )
+        }
+    };
+
+    visiting.remove(monkey_id);
+    Ok(expression)
+}
+
+pub fn build_expression(
+    jobs: &HashMap<MonkeyId, Something>,
+    root: &str,
+    unknown: &str,
+) -> Result<Expression, SolveError> {
+    build(jobs, root, unknown, &mut HashSet::new())
+}
+
+pub fn simplify(expression: Expression) -> Result<Expression, SolveError> {
+    use Expression::{Binary, Constant};
+
+    let (operator, a, b) = match expression {
+        Binary(operator, a, b) => (operator, simplify(*a)?, simplify(*b)?),
+        _ => return Ok(expression),
+    };
+
+    let simplified = match (operator, a, b) {
+        (operator, Constant(a), Constant(b)) => Constant(apply(operator, a, b)?),
+        (_, _, Constant(b)) if operator == Operator::Divide && b.is_zero() => {
+            return Err(SolveError::DivisionByZero)
+        }
+        (Operator::Add, Constant(zero), other)
+        | (Operator::Add | Operator::Subtract, other, Constant(zero))
+            if zero.is_zero() =>
+        {
+            other
+        }
+        (Operator::Multiply, Constant(one), other)
+        | (Operator::Multiply | Operator::Divide, other, Constant(one))
+            if one == Rational::ONE =>
+        {
+            other
+        }
+        (operator, a, b) => Binary(operator, Box::new(a), Box::new(b)),
+    };
+
+    Ok(simplified)
+}
+
+fn linearise(expression: &Expression) -> Result<Linear, SolveError> {
+    match expression {
+        Expression::Constant(value) => Ok(Linear {
+            coefficient: Rational::ZERO,
+            constant: *value,
+        }),
+        Expression::Unknown(_) => Ok(Linear {
+            coefficient: Rational::ONE,
+            constant: Rational::ZERO,
+        }),
+        Expression::Binary(operator, a, b) => {
+            let (a, b) = (linearise(a)?, linearise(b)?);
+
+            match operator {
+                Operator::Add | Operator::Subtract => Ok(Linear {
+                    coefficient: apply(*operator, a.coefficient, b.coefficient)?,
+                    constant: apply(*operator, a.constant, b.constant)?,
+                }),
+                Operator::Multiply => {
+                    let (scale, linear) = match (a.coefficient.is_zero(), b.coefficient.is_zero()) {
+                        (true, _) => (a.constant, b),
+                        (_, true) => (b.constant, a),
+                        _ => return Err(SolveError::NonLinear),
+                    };
+
+                    Ok(Linear {
+                        coefficient: apply(Operator::Multiply, linear.coefficient, scale)?,
+                        constant: apply(Operator::Multiply, linear.constant, scale)?,
+                    })
+                }
+                Operator::Divide => {
+                    if !b.coefficient.is_zero() {
+                        return Err(SolveError::NonLinear);
+                    }
+
+                    Ok(Linear {
+                        coefficient: apply(Operator::Divide, a.coefficient, b.constant)?,
+                        constant: apply(Operator::Divide, a.constant, b.constant)?,
+                    })
+                }
+            }
+        }
+    }
+}
+
+// Finds the yell of `unknown` that makes both operands of `root`'s job equal.
+pub fn solve(
+    jobs: &HashMap<MonkeyId, Something>,
+    root: &str,
+    unknown: &str,
+) -> Result<Value, SolveError> {
+    if !jobs.contains_key(unknown) {
+        return Err(SolveError::MissingMonkey(unknown.to_string()));
+    }
+
+    let (a, b) = match jobs.get(root) {
+        None => return Err(SolveError::MissingMonkey(root.to_string())),
+        Some(Something::MathsOperation(operation)) if root != unknown => {
+            let (_, a, b) = split_operation(operation);
+            (a, b)
+        }
+        Some(_) => return Err(SolveError::NotAnEquation(root.to_string())),
+    };
+
+    let lhs = simplify(build_expression(jobs, a, unknown)?)?;
+    let rhs = simplify(build_expression(jobs, b, unknown)?)?;
+    log::debug!("{} = {}", lhs, rhs);
+
+    let (lhs, rhs) = (linearise(&lhs)?, linearise(&rhs)?);
+    let coefficient = apply(Operator::Subtract, lhs.coefficient, rhs.coefficient)?;
+    let constant = apply(Operator::Subtract, rhs.constant, lhs.constant)?;
+
+    if coefficient.is_zero() {
+        return match constant.is_zero() {
+            true => Err(SolveError::InfiniteSolutions),
+            false => Err(SolveError::NoSolution),
+        };
+    }
+
+    let solution = apply(Operator::Divide, constant, coefficient)?;
+
+    match solution.to_integer() {
+        Some(value) => Value::try_from(value).map_err(|_| SolveError::Overflow),
+        None => Err(SolveError::NoIntegerSolution(solution)),
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::Constant(value) => write!(f, "{}", value),
+            Expression::Unknown(monkey_id) => write!(f, "{}", monkey_id),
+            Expression::Binary(operator, a, b) => {
+                let symbol = match operator {
+                    Operator::Add => '+',
+                    Operator::Subtract => '-',
+                    Operator::Multiply => '*',
+                    Operator::Divide => '/',
+                };
+                write!(f, "({} {} {})", a, symbol, b)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_jobs, rational::Rational};
+
+    use super::{build_expression, simplify, solve, SolveError};
+
+    #[test]
+    fn test_simplify() {
+        let jobs = parse_jobs(include_str!("../test.txt")).unwrap();
+
+        let expression = build_expression(&jobs, "pppw", "humn").unwrap();
+        assert_eq!("((4 + (2 * (humn - 3))) / 4)", expression.to_string());
+
+        let expression = build_expression(&jobs, "sjmn", "humn").unwrap();
+        assert_eq!("((32 - 2) * 5)", expression.to_string());
+        assert_eq!("150", simplify(expression).unwrap().to_string());
+
+        let jobs =
+            parse_jobs("root: a / b\na: c * humn\nb: 1\nc: d + e\nd: 0\ne: 1\nhumn: 1").unwrap();
+        let expression = build_expression(&jobs, "root", "humn").unwrap();
+        assert_eq!("humn", simplify(expression).unwrap().to_string());
+    }
+
+    #[test]
+    fn test_solve_any_unknown() {
+        let jobs = parse_jobs(include_str!("../test.txt")).unwrap();
+
+        assert_eq!(Ok(301), solve(&jobs, "root", "humn"));
+        assert_eq!(Ok(5), solve(&jobs, "sjmn", "drzm"));
+        assert_eq!(Ok(32), solve(&jobs, "drzm", "zczc"));
+        assert_eq!(Ok(8), solve(&jobs, "pppw", "lfqf"));
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        let jobs =
+            parse_jobs("root: a + b\na: humn * c\nc: humn - d\nd: 1\nb: 3\nhumn: 1").unwrap();
+        assert_eq!(Err(SolveError::NonLinear), solve(&jobs, "root", "humn"));
+
+        let jobs =
+            parse_jobs("root: a + b\na: c / d\nc: humn * e\nd: 2\ne: 3\nb: 4\nhumn: 1").unwrap();
+        assert_eq!(
+            Err(SolveError::NoIntegerSolution(Rational::new(8, 3).unwrap())),
+            solve(&jobs, "root", "humn")
+        );
+
+        let jobs =
+            parse_jobs("root: a + b\na: c / d\nc: humn * e\nd: 2\ne: 3\nb: 6\nhumn: 1").unwrap();
+        assert_eq!(Ok(4), solve(&jobs, "root", "humn"));
+
+        let jobs = parse_jobs("root: a + b\na: humn - humn\nb: 3\nhumn: 1").unwrap();
+        assert_eq!(Err(SolveError::NoSolution), solve(&jobs, "root", "humn"));
+
+        let jobs = parse_jobs("root: a + b\na: humn - humn\nb: 0\nhumn: 1").unwrap();
+        assert_eq!(
+            Err(SolveError::InfiniteSolutions),
+            solve(&jobs, "root", "humn")
+        );
+
+        let jobs = parse_jobs("root: a + b\na: humn - c\nb: 0\nhumn: 1").unwrap();
+        assert_eq!(
+            Err(SolveError::MissingMonkey("c".to_string())),
+            solve(&jobs, "root", "humn")
+        );
+
+        let jobs = parse_jobs("root: a + b\na: 1\nb: 0\nhumn: 1").unwrap();
+        assert_eq!(
+            Err(SolveError::NotAnEquation("a".to_string())),
+            solve(&jobs, "a", "humn")
+        );
+    }
+}
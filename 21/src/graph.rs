@@ -0,0 +1,283 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Write,
+};
+
+use crate::{MonkeyId, Operation, Something, Value};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum JobError {
+    MissingMonkey {
+        monkey_id: MonkeyId,
+        referenced_by: Option<MonkeyId>,
+    },
+    Cycle(Vec<MonkeyId>),
+    DivisionByZero(MonkeyId),
+    Overflow(MonkeyId),
+}
+
+fn dependencies(job: &Something) -> Vec<&str> {
+    match job {
+        Something::Number(_) => vec![],
+        Something::MathsOperation(Operation::Add(a, b))
+        | Something::MathsOperation(Operation::Subtract(a, b))
+        | Something::MathsOperation(Operation::Divide(a, b))
+        | Something::MathsOperation(Operation::Multiply(a, b)) => vec![a, b],
+    }
+}
+
+fn get_job<'a>(
+    jobs: &'a HashMap<MonkeyId, Something>,
+    monkey_id: &str,
+    referenced_by: Option<&str>,
+) -> Result<&'a Something, JobError> {
+    jobs.get(monkey_id).ok_or_else(|| JobError::MissingMonkey {
+        monkey_id: monkey_id.to_string(),
+        referenced_by: referenced_by.map(|id| id.to_string()),
+    })
+}
+
+// Every monkey left over by Kahn's algorithm still waits on another left-over
+// monkey, so following those waits from any of them must end up going round a cycle.
+fn find_cycle<'a>(
+    jobs: &'a HashMap<MonkeyId, Something>,
+    remaining: &HashMap<&'a str, usize>,
+) -> Vec<MonkeyId> {
+    let mut current = remaining
+        .iter()
+        .filter(|(_, count)| **count > 0)
+        .map(|(monkey_id, _)| *monkey_id)
+        .min()
+        .unwrap();
+    let mut path: Vec<&str> = Vec::new();
+
+    while !path.contains(&current) {
+        path.push(current);
+        current = dependencies(&jobs[current])
+            .into_iter()
+            .find(|dependency| remaining[dependency] > 0)
+            .unwrap();
+    }
+
+    let start = path
+        .iter()
+        .position(|monkey_id| *monkey_id == current)
+        .unwrap();
+    path[start..]
+        .iter()
+        .map(|monkey_id| monkey_id.to_string())
+        .collect()
+}
+
+pub fn get_execution_order<'a>(
+    jobs: &'a HashMap<MonkeyId, Something>,
+    root: &'a str,
+) -> Result<Vec<&'a str>, JobError> {
+    get_job(jobs, root, None)?;
+
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut remaining: HashMap<&str, usize> = HashMap::new();
+    let mut seen = HashSet::from([root]);
+    let mut stack = vec![root];
+
+    while let Some(monkey_id) = stack.pop() {
+        let dependencies = dependencies(get_job(jobs, monkey_id, None)?);
+        remaining.insert(monkey_id, dependencies.len());
+
+        for dependency in dependencies {
+            get_job(jobs, dependency, Some(monkey_id))?;
+            dependents.entry(dependency).or_default().push(monkey_id);
+
+            if seen.insert(dependency) {
+                stack.push(dependency);
+            }
+        }
+    }
+
+    let mut queue: VecDeque<&str> = remaining
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(monkey_id, _)| *monkey_id)
+        .collect();
+    let mut order = Vec::with_capacity(remaining.len());
+
+    while let Some(monkey_id) = queue.pop_front() {
+        order.push(monkey_id);
+
+        for dependent in dependents.get(monkey_id).into_iter().flatten() {
+            let count = remaining.get_mut(dependent).unwrap();
+            *count -= 1;
+
+            if *count == 0 {
+                queue.push_back(dependent);
+            }
+        }
+    }
+
+    if order.len() < remaining.len() {
+        return Err(JobError::Cycle(find_cycle(jobs, &remaining)));
+    }
+
+    log::trace!("{:?}", order);
+
+    Ok(order)
+}
+
+pub fn get_yell(jobs: &HashMap<MonkeyId, Something>, root: &str) -> Result<Value, JobError> {
+    let mut yells: HashMap<&str, Value> = HashMap::new();
+
+    for monkey_id in get_execution_order(jobs, root)? {
+        let overflow = || JobError::Overflow(monkey_id.to_string());
+
+        let yell = match &jobs[monkey_id] {
+            Something::Number(value) => *value,
+            Something::MathsOperation(operation) => match operation {
+                Operation::Add(a, b) => yells[a.as_str()]
+                    .checked_add(yells[b.as_str()])
+                    .ok_or_else(overflow)?,
+                Operation::Subtract(a, b) => yells[a.as_str()]
+                    .checked_sub(yells[b.as_str()])
+                    .ok_or_else(overflow)?,
+                Operation::Multiply(a, b) => yells[a.as_str()]
+                    .checked_mul(yells[b.as_str()])
+                    .ok_or_else(overflow)?,
+                Operation::Divide(a, b) => {
+                    if yells[b.as_str()] == 0 {
+                        return Err(JobError::DivisionByZero(monkey_id.to_string()));
+                    }
+
+                    yells[a.as_str()]
+                        .checked_div(yells[b.as_str()])
+                        .ok_or_else(overflow)?
+                }
+            },
+        };
+
+        yells.insert(monkey_id, yell);
+    }
+
+    Ok(yells[root])
+}
+
+pub fn to_dot(jobs: &HashMap<MonkeyId, Something>) -> String {
+    let mut monkey_ids: Vec<&MonkeyId> = jobs.keys().collect();
+    monkey_ids.sort();
+
+    let mut dot = String::from("digraph jobs {\n");
+
+    for monkey_id in monkey_ids {
+        let label = match &jobs[monkey_id] {
+            Something::Number(value) => value.to_string(),
+            Something::MathsOperation(operation) => {
+                let (a, symbol, b) = match operation {
+                    Operation::Add(a, b) => (a, '+', b),
+                    Operation::Subtract(a, b) => (a, '-', b),
+                    Operation::Multiply(a, b) => (a, '*', b),
+                    Operation::Divide(a, b) => (a, '/', b),
+                };
+                format!("{} {} {}", a, symbol, b)
+            }
+        };
+
+        writeln!(
+            dot,
+            "    {} [label=\"{}: {}\"];",
+            monkey_id, monkey_id, label
+        )
+        .unwrap();
+
+        for dependency in dependencies(&jobs[monkey_id]) {
+            writeln!(dot, "    {} -> {};", monkey_id, dependency).unwrap();
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_jobs;
+
+    use super::{get_execution_order, get_yell, to_dot, JobError};
+
+    #[test]
+    fn test_get_execution_order() {
+        let jobs = parse_jobs(include_str!("../test.txt")).unwrap();
+        let order = get_execution_order(&jobs, "root").unwrap();
+
+        assert_eq!(15, order.len());
+        assert_eq!(Some(&"root"), order.last());
+
+        let position = |monkey_id| order.iter().position(|id| *id == monkey_id).unwrap();
+        assert!(position("humn") < position("ptdq"));
+        assert!(position("ptdq") < position("lgvd"));
+        assert!(position("lgvd") < position("cczh"));
+
+        let order = get_execution_order(&jobs, "drzm").unwrap();
+        assert_eq!(3, order.len());
+    }
+
+    #[test]
+    fn test_missing_monkey() {
+        let jobs = parse_jobs("root: a + b\na: 1").unwrap();
+
+        assert_eq!(
+            Err(JobError::MissingMonkey {
+                monkey_id: "b".to_string(),
+                referenced_by: Some("root".to_string()),
+            }),
+            get_yell(&jobs, "root")
+        );
+        assert_eq!(
+            Err(JobError::MissingMonkey {
+                monkey_id: "c".to_string(),
+                referenced_by: None,
+            }),
+            get_yell(&jobs, "c")
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let jobs = parse_jobs("root: a + b\na: 1\nb: c * d\nc: d - e\nd: 2\ne: b / d").unwrap();
+
+        assert_eq!(
+            Err(JobError::Cycle(vec![
+                "b".to_string(),
+                "c".to_string(),
+                "e".to_string()
+            ])),
+            get_yell(&jobs, "root")
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_errors() {
+        let jobs = parse_jobs("root: a / b\na: 1\nb: c - c\nc: 2").unwrap();
+        assert_eq!(
+            Err(JobError::DivisionByZero("root".to_string())),
+            get_yell(&jobs, "root")
+        );
+
+        let jobs = parse_jobs("root: a * a\na: 9223372036854775807").unwrap();
+        assert_eq!(
+            Err(JobError::Overflow("root".to_string())),
+            get_yell(&jobs, "root")
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let jobs = parse_jobs("root: a + b\na: 1\nb: 2").unwrap();
+        let expected = "digraph jobs {
+    a [label=\"a: 1\"];
+    b [label=\"b: 2\"];
+    root [label=\"root: a + b\"];
+    root -> a;
+    root -> b;
+}
+";
+        assert_eq!(expected, to_dot(&jobs));
+    }
+}
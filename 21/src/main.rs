@@ -1,7 +1,11 @@
-use std::{
-    collections::{HashMap, VecDeque},
-    env, fs,
-};
+mod expression;
+mod graph;
+mod rational;
+
+use std::{collections::HashMap, env, fs};
+
+use expression::solve;
+use graph::{get_yell, to_dot};
 
 type Value = i64;
 type MonkeyId = String;
@@ -31,227 +35,96 @@ fn load_input() -> String {
     fs::read_to_string(args.get(1).unwrap()).expect("Should have been able to read the file")
 }
 
-fn parse_operation(input: &str) -> Operation {
-    let mut split = input.split(' ');
-    let a = split.next().unwrap().to_string();
-    let op = split.next().unwrap();
-    let b = split.next().unwrap().to_string();
-
-    match op {
-        "+" => Operation::Add(a, b),
-        "-" => Operation::Subtract(a, b),
-        "*" => Operation::Multiply(a, b),
-        "/" => Operation::Divide(a, b),
-        _ => panic!("Unknown Operator whilst parsing!"),
-    }
+fn load_output_format() -> Option<String> {
+    env::args().nth(2)
 }
 
-fn parse_job(input: &str) -> Job {
-    let mut split = input.split(": ");
-    let monkey_id = split.next().unwrap().to_string();
+#[derive(Debug, PartialEq, Eq)]
+struct ParseJobError {
+    line: usize,
+    msg: String,
+}
 
-    let yell_str = split.next().unwrap();
-    let determine_yell: char = yell_str.chars().next().unwrap();
+fn parse_operation(input: &str) -> Result<Operation, String> {
+    let parts: Vec<&str> = input.split(' ').collect();
 
-    let yell = match determine_yell.is_ascii_digit() {
-        true => Something::Number(yell_str.parse::<Value>().unwrap()),
-        false => Something::MathsOperation(parse_operation(yell_str)),
+    let (a, op, b) = match parts[..] {
+        [a, op, b] => (a.to_string(), op, b.to_string()),
+        _ => return Err(format!("Expected an operation but found '{}'", input)),
     };
 
-    Job { monkey_id, yell }
-}
-
-fn parse_jobs(input: &str) -> HashMap<MonkeyId, Something> {
-    HashMap::from_iter(
-        input
-            .split('\n')
-            .map(parse_job)
-            .map(|job| (job.monkey_id, job.yell)),
-    )
-}
-
-fn get_execution_order(job_map: &HashMap<MonkeyId, Something>, root: &str) -> Vec<MonkeyId> {
-    let mut queue = VecDeque::new();
-    let mut order = Vec::new();
-
-    queue.push_front(job_map.get(root).unwrap());
-    order.push(root.to_string());
-
-    while !queue.is_empty() {
-        let job = queue.pop_back().unwrap();
-
-        let maybe_children = match job {
-            Something::Number(_) => None,
-            Something::MathsOperation(Operation::Add(a, b))
-            | Something::MathsOperation(Operation::Subtract(a, b))
-            | Something::MathsOperation(Operation::Divide(a, b))
-            | Something::MathsOperation(Operation::Multiply(a, b)) => Some([a, b]),
-        };
-
-        if let Some(children) = maybe_children {
-            for child in children.into_iter() {
-                if order.contains(child) {
-                    order.remove(order.iter().position(|a| a == child).unwrap());
-                }
-
-                order.push(child.to_string());
-                queue.push_front(job_map.get(child).unwrap())
-            }
-        }
+    match op {
+        "+" => Ok(Operation::Add(a, b)),
+        "-" => Ok(Operation::Subtract(a, b)),
+        "*" => Ok(Operation::Multiply(a, b)),
+        "/" => Ok(Operation::Divide(a, b)),
+        _ => Err(format!("Unknown operator '{}'", op)),
     }
-
-    order.reverse();
-    log::trace!("{:?}", order);
-
-    order
 }
 
-fn unwrap_value(jobs: &HashMap<MonkeyId, Something>, monkey_id: &str) -> Value {
-    let something = jobs.get(monkey_id).unwrap();
-
-    if let Something::Number(v) = something {
-        return *v;
-    }
+fn parse_job(input: &str) -> Result<Job, String> {
+    let (monkey_id, yell_str) = input
+        .split_once(": ")
+        .ok_or_else(|| format!("Expected 'name: job' but found '{}'", input))?;
+
+    let is_number = yell_str
+        .trim_start_matches('-')
+        .starts_with(|c: char| c.is_ascii_digit());
+
+    let yell = match is_number {
+        true => Something::Number(
+            yell_str
+                .parse::<Value>()
+                .map_err(|_| format!("Invalid number '{}'", yell_str))?,
+        ),
+        false => Something::MathsOperation(parse_operation(yell_str)?),
+    };
 
-    panic!("Expected to unwrap a Number not an expression!");
+    Ok(Job {
+        monkey_id: monkey_id.to_string(),
+        yell,
+    })
 }
 
-fn get_yell(job_map_read: &HashMap<MonkeyId, Something>, root: &str) -> Value {
-    let mut job_map: HashMap<MonkeyId, Something> = job_map_read.clone();
-
-    let execution_order = get_execution_order(&job_map, root);
-
-    log::trace!("{:#?}", execution_order);
-
-    for monkey_id in execution_order {
-        let something = job_map.get(&monkey_id).unwrap();
-
-        if let Something::MathsOperation(operation) = something {
-            let result = match operation {
-                Operation::Add(a, b) => unwrap_value(&job_map, a) + unwrap_value(&job_map, b),
-                Operation::Subtract(a, b) => unwrap_value(&job_map, a) - unwrap_value(&job_map, b),
-                Operation::Multiply(a, b) => unwrap_value(&job_map, a) * unwrap_value(&job_map, b),
-                Operation::Divide(a, b) => unwrap_value(&job_map, a) / unwrap_value(&job_map, b),
-            };
+fn parse_jobs(input: &str) -> Result<HashMap<MonkeyId, Something>, ParseJobError> {
+    let mut jobs = HashMap::new();
 
-            job_map.insert(monkey_id, Something::Number(result));
+    for (i, line) in input.split('\n').enumerate() {
+        if line.is_empty() {
+            continue;
         }
-    }
-
-    unwrap_value(&job_map, root)
-}
 
-fn find_path(job_map: &HashMap<MonkeyId, Something>, start: &str, goal: &str) -> Vec<MonkeyId> {
-    let mut stack = Vec::new();
+        let error = |msg| ParseJobError { line: i + 1, msg };
+        let job = parse_job(line).map_err(error)?;
 
-    stack.push((start, vec![]));
-
-    while !stack.is_empty() {
-        let (id, path) = stack.pop().unwrap();
-
-        if id == goal {
-            let mut new_path = path;
-            new_path.push(id.to_string());
-            return new_path;
+        if jobs.contains_key(&job.monkey_id) {
+            return Err(error(format!("Duplicate job for '{}'", job.monkey_id)));
         }
 
-        let job = job_map.get(id).unwrap();
-
-        let maybe_children = match job {
-            Something::Number(_) => None,
-            Something::MathsOperation(Operation::Add(a, b))
-            | Something::MathsOperation(Operation::Subtract(a, b))
-            | Something::MathsOperation(Operation::Divide(a, b))
-            | Something::MathsOperation(Operation::Multiply(a, b)) => Some([a, b]),
-        };
-
-        if let Some(children) = maybe_children {
-            for child in children.into_iter() {
-                let mut new_path = path.clone();
-                new_path.push(id.to_string());
-                stack.push((child, new_path));
-            }
-        }
+        jobs.insert(job.monkey_id, job.yell);
     }
 
-    panic!("No humn found!");
+    Ok(jobs)
 }
 
 fn get_humn_yell(jobs: &HashMap<MonkeyId, Something>) -> Value {
-    let path_to_humn = find_path(jobs, "root", "humn");
-    log::debug!("{:?}", path_to_humn);
-
-    let root_job = jobs.get("root").unwrap();
-    let maybe_children = match root_job {
-        Something::Number(_) => None,
-        Something::MathsOperation(Operation::Add(a, b))
-        | Something::MathsOperation(Operation::Subtract(a, b))
-        | Something::MathsOperation(Operation::Divide(a, b))
-        | Something::MathsOperation(Operation::Multiply(a, b)) => Some([a, b]),
-    };
-
-    let child = path_to_humn.get(1).unwrap();
-    let other = maybe_children
-        .unwrap()
-        .into_iter()
-        .find(|a| *a != child)
-        .unwrap();
-    let mut humn_yell = get_yell(jobs, other);
-    log::debug!("Yell: {}", humn_yell);
-
-    for i in 1..(path_to_humn.len() - 1) {
-        let parent = path_to_humn.get(i).unwrap();
-        let child = path_to_humn.get(i + 1).unwrap();
-
-        log::debug!("Parent: {}, Child: {}", parent, child);
-
-        if let Something::MathsOperation(parent_operation) = jobs.get(parent).unwrap() {
-            log::debug!("{:?}", parent_operation);
-
-            if let Operation::Add(a, b) = parent_operation {
-                if a == child {
-                    humn_yell -= get_yell(jobs, b);
-                } else {
-                    humn_yell -= get_yell(jobs, a);
-                }
-            }
-
-            if let Operation::Subtract(a, b) = parent_operation {
-                if a == child {
-                    humn_yell += get_yell(jobs, b);
-                } else {
-                    humn_yell = get_yell(jobs, a) - humn_yell;
-                }
-            }
-
-            if let Operation::Multiply(a, b) = parent_operation {
-                if a == child {
-                    humn_yell /= get_yell(jobs, b);
-                } else {
-                    humn_yell /= get_yell(jobs, a);
-                }
-            }
-
-            if let Operation::Divide(a, b) = parent_operation {
-                if a == child {
-                    humn_yell *= get_yell(jobs, b);
-                } else {
-                    humn_yell = get_yell(jobs, a) / humn_yell;
-                }
-            }
-        }
-        log::debug!("Yell: {}", humn_yell);
-    }
-
-    humn_yell
+    solve(jobs, "root", "humn").unwrap()
 }
 
 fn main() {
     env_logger::init();
     let input = load_input();
-    let jobs = parse_jobs(&input);
+    let jobs = parse_jobs(&input).unwrap();
 
-    let root_yell = get_yell(&jobs, "root");
+    if let Some(format) = load_output_format() {
+        match format.as_str() {
+            "--dot" => print!("{}", to_dot(&jobs)),
+            _ => panic!("Unknown output format {}", format),
+        }
+        return;
+    }
+
+    let root_yell = get_yell(&jobs, "root").unwrap();
     println!("{}", root_yell);
 
     let humn_yell = get_humn_yell(&jobs);
@@ -262,7 +135,9 @@ fn main() {
 mod tests {
     use std::collections::HashMap;
 
-    use crate::{get_humn_yell, get_yell, parse_jobs, Job, MonkeyId, Operation, Something};
+    use crate::{
+        get_humn_yell, get_yell, parse_jobs, Job, MonkeyId, Operation, ParseJobError, Something,
+    };
 
     fn init() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -362,14 +237,14 @@ mod tests {
         let input = include_str!("../test.txt");
         let expected = get_test_jobs();
         let actual = parse_jobs(input);
-        assert_eq!(expected, actual);
+        assert_eq!(Ok(expected), actual);
     }
 
     #[test]
     fn test_get_root_yell() {
         init();
         let input = get_test_jobs();
-        let expected = 152;
+        let expected = Ok(152);
         let actual = get_yell(&input, "root");
         assert_eq!(expected, actual);
     }
@@ -382,4 +257,31 @@ mod tests {
         let actual = get_humn_yell(&input);
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_parse_errors() {
+        init();
+
+        assert_eq!(
+            Err(ParseJobError {
+                line: 2,
+                msg: "Unknown operator '%'".to_string(),
+            }),
+            parse_jobs("root: a + b\na: b % c")
+        );
+        assert_eq!(
+            Err(ParseJobError {
+                line: 1,
+                msg: "Expected 'name: job' but found 'root'".to_string(),
+            }),
+            parse_jobs("root")
+        );
+        assert_eq!(
+            Err(ParseJobError {
+                line: 3,
+                msg: "Duplicate job for 'a'".to_string(),
+            }),
+            parse_jobs("root: a + a\na: 1\na: 2\n")
+        );
+    }
 }
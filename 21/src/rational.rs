@@ -0,0 +1,122 @@
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational {
+        numerator: 0,
+        denominator: 1,
+    };
+    pub const ONE: Rational = Rational {
+        numerator: 1,
+        denominator: 1,
+    };
+
+    pub fn new(numerator: i128, denominator: i128) -> Option<Rational> {
+        if denominator == 0 {
+            return None;
+        }
+
+        let divisor = gcd(numerator, denominator);
+        let sign = if denominator < 0 { -1 } else { 1 };
+
+        Some(Rational {
+            numerator: sign * (numerator / divisor),
+            denominator: sign * (denominator / divisor),
+        })
+    }
+
+    pub fn integer(value: i128) -> Rational {
+        Rational {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+
+    pub fn to_integer(self) -> Option<i128> {
+        match self.denominator {
+            1 => Some(self.numerator),
+            _ => None,
+        }
+    }
+
+    pub fn checked_add(self, rhs: Rational) -> Option<Rational> {
+        let divisor = gcd(self.denominator, rhs.denominator);
+        let left = self.numerator.checked_mul(rhs.denominator / divisor)?;
+        let right = rhs.numerator.checked_mul(self.denominator / divisor)?;
+        let denominator = (self.denominator / divisor).checked_mul(rhs.denominator)?;
+        Rational::new(left.checked_add(right)?, denominator)
+    }
+
+    pub fn checked_neg(self) -> Option<Rational> {
+        Some(Rational {
+            numerator: self.numerator.checked_neg()?,
+            denominator: self.denominator,
+        })
+    }
+
+    pub fn checked_sub(self, rhs: Rational) -> Option<Rational> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    pub fn checked_mul(self, rhs: Rational) -> Option<Rational> {
+        // Cross-reduce first to keep the intermediate products small.
+        let a = gcd(self.numerator, rhs.denominator);
+        let b = gcd(rhs.numerator, self.denominator);
+        let numerator = (self.numerator / a).checked_mul(rhs.numerator / b)?;
+        let denominator = (self.denominator / b).checked_mul(rhs.denominator / a)?;
+        Rational::new(numerator, denominator)
+    }
+
+    pub fn checked_div(self, rhs: Rational) -> Option<Rational> {
+        let reciprocal = Rational::new(rhs.denominator, rhs.numerator)?;
+        self.checked_mul(reciprocal)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.denominator {
+            1 => write!(f, "{}", self.numerator),
+            _ => write!(f, "{}/{}", self.numerator, self.denominator),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rational;
+
+    #[test]
+    fn test_rational_arithmetic() {
+        let half = Rational::new(2, 4).unwrap();
+        let third = Rational::new(-1, -3).unwrap();
+
+        assert_eq!(Rational::new(1, 2), Some(half));
+        assert_eq!(Rational::new(5, 6), half.checked_add(third));
+        assert_eq!(Rational::new(1, 6), half.checked_sub(third));
+        assert_eq!(Rational::new(1, 6), half.checked_mul(third));
+        assert_eq!(Rational::new(3, 2), half.checked_div(third));
+        assert_eq!(None, half.checked_div(Rational::ZERO));
+        assert_eq!(None, Rational::new(1, 0));
+        assert_eq!(Some(2), Rational::new(-4, -2).unwrap().to_integer());
+        assert_eq!(None, half.to_integer());
+        assert_eq!("-1/2", Rational::new(1, -2).unwrap().to_string());
+    }
+}
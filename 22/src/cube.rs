@@ -0,0 +1,344 @@
+use std::collections::{HashMap, VecDeque};
+
+use nalgebra::Vector3;
+
+use crate::{Connection, Coordinate, Edge, Facing, MapPartConnection, Net};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FoldError {
+    FaceCount(usize),
+    Disconnected,
+    Overlap { face: usize, other: usize },
+}
+
+// Where a face of the net ends up once folded: `right` and `down` point along the
+// face's +x and +y board directions and `normal` points out of the cube.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Frame {
+    pub normal: Vector3<i32>,
+    pub right: Vector3<i32>,
+    pub down: Vector3<i32>,
+}
+
+impl Frame {
+    // Folds the net along the edge in direction `facing`, so the neighbouring face
+    // bends away from the viewer onto the side of the cube.
+    fn fold(&self, facing: Facing) -> Frame {
+        match facing {
+            Facing::Right => Frame {
+                normal: self.right,
+                right: -self.normal,
+                down: self.down,
+            },
+            Facing::Left => Frame {
+                normal: -self.right,
+                right: self.normal,
+                down: self.down,
+            },
+            Facing::Down => Frame {
+                normal: self.down,
+                right: self.right,
+                down: -self.normal,
+            },
+            Facing::Up => Frame {
+                normal: -self.down,
+                right: self.right,
+                down: self.normal,
+            },
+        }
+    }
+
+    pub fn direction(&self, facing: Facing) -> Vector3<i32> {
+        match facing {
+            Facing::Right => self.right,
+            Facing::Down => self.down,
+            Facing::Left => -self.right,
+            Facing::Up => -self.down,
+        }
+    }
+
+    pub fn facing(&self, direction: Vector3<i32>) -> Facing {
+        FACINGS
+            .into_iter()
+            .find(|&facing| self.direction(facing) == direction)
+            .unwrap()
+    }
+
+    fn edge_towards(&self, direction: Vector3<i32>) -> Edge {
+        if direction == -self.down {
+            Edge::Top
+        } else if direction == self.right {
+            Edge::Right
+        } else if direction == self.down {
+            Edge::Bottom
+        } else {
+            Edge::Left
+        }
+    }
+}
+
+const FACINGS: [Facing; 4] = [Facing::Right, Facing::Down, Facing::Left, Facing::Up];
+
+fn neighbour(net: &Net, start: Coordinate, facing: Facing) -> Option<u8> {
+    let (x, y) = (start.x as i64, start.y as i64);
+    let (x, y) = match facing {
+        Facing::Right => (x + 1, y),
+        Facing::Down => (x, y + 1),
+        Facing::Left => (x - 1, y),
+        Facing::Up => (x, y - 1),
+    };
+
+    if x < 0 || y < 0 || x as usize >= net.ncols() || y as usize >= net.nrows() {
+        return None;
+    }
+
+    net[(y as usize, x as usize)]
+}
+
+pub fn fold_frames(starts: &[Coordinate], net: &Net) -> Result<Vec<Frame>, FoldError> {
+    if starts.len() != 6 {
+        return Err(FoldError::FaceCount(starts.len()));
+    }
+
+    let mut frames: Vec<Option<Frame>> = vec![None; starts.len()];
+    frames[0] = Some(Frame {
+        normal: Vector3::new(0, 0, -1),
+        right: Vector3::new(1, 0, 0),
+        down: Vector3::new(0, 1, 0),
+    });
+
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(face) = queue.pop_front() {
+        let frame = frames[face].unwrap();
+
+        for facing in FACINGS {
+            if let Some(next) = neighbour(net, starts[face], facing) {
+                let next = next as usize;
+
+                if frames[next].is_none() {
+                    frames[next] = Some(frame.fold(facing));
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    let frames: Vec<Frame> = frames
+        .into_iter()
+        .collect::<Option<_>>()
+        .ok_or(FoldError::Disconnected)?;
+
+    for (face, frame) in frames.iter().enumerate() {
+        if let Some(other) = frames[..face]
+            .iter()
+            .position(|other| other.normal == frame.normal)
+        {
+            return Err(FoldError::Overlap { face, other });
+        }
+    }
+
+    Ok(frames)
+}
+
+pub fn fold_cube(starts: &[Coordinate], net: &Net) -> Result<Vec<MapPartConnection>, FoldError> {
+    let frames = fold_frames(starts, net)?;
+
+    let connections = frames
+        .iter()
+        .map(|frame| {
+            HashMap::from(FACINGS.map(|facing| {
+                let direction = frame.direction(facing);
+                let part_id = frames
+                    .iter()
+                    .position(|other| other.normal == direction)
+                    .unwrap();
+                let edge = frames[part_id].edge_towards(frame.normal);
+
+                (
+                    facing,
+                    Connection {
+                        part_id,
+                        edge,
+                        reversed: false,
+                    },
+                )
+            }))
+        })
+        .collect();
+
+    log::debug!("Connections: {:#?}", connections);
+
+    Ok(connections)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        move_across_map, parse_map,
+        topology::{Cube, TopologyError},
+        Coordinate, Facing, Map, State,
+    };
+
+    use super::FoldError;
+
+    type Cell = (i32, i32);
+    type Shape = Vec<Cell>;
+
+    fn normalise(shape: &[Cell]) -> Shape {
+        let min_x = shape.iter().map(|(x, _)| *x).min().unwrap();
+        let min_y = shape.iter().map(|(_, y)| *y).min().unwrap();
+        let mut normalised: Shape = shape.iter().map(|(x, y)| (x - min_x, y - min_y)).collect();
+        normalised.sort();
+        normalised
+    }
+
+    fn get_fixed_hexominoes() -> HashSet<Shape> {
+        let mut shapes = HashSet::from([vec![(0, 0)]]);
+
+        for _ in 1..6 {
+            let mut grown = HashSet::new();
+
+            for shape in shapes.iter() {
+                for (x, y) in shape.iter() {
+                    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                        let cell = (x + dx, y + dy);
+
+                        if !shape.contains(&cell) {
+                            let mut bigger = shape.clone();
+                            bigger.push(cell);
+                            grown.insert(normalise(&bigger));
+                        }
+                    }
+                }
+            }
+
+            shapes = grown;
+        }
+
+        shapes
+    }
+
+    fn get_canonical_form(shape: &Shape) -> Shape {
+        let symmetries: [fn(Cell) -> Cell; 8] = [
+            |(x, y)| (x, y),
+            |(x, y)| (-y, x),
+            |(x, y)| (-x, -y),
+            |(x, y)| (y, -x),
+            |(x, y)| (-x, y),
+            |(x, y)| (y, x),
+            |(x, y)| (x, -y),
+            |(x, y)| (-y, -x),
+        ];
+
+        symmetries
+            .iter()
+            .map(|symmetry| normalise(&shape.iter().cloned().map(symmetry).collect::<Shape>()))
+            .min()
+            .unwrap()
+    }
+
+    fn draw_board(shape: &Shape, length: usize) -> String {
+        let width = shape.iter().map(|(x, _)| *x).max().unwrap() as usize + 1;
+        let height = shape.iter().map(|(_, y)| *y).max().unwrap() as usize + 1;
+
+        let mut rows = Vec::new();
+        for y in 0..height * length {
+            let row: String = (0..width * length)
+                .map(|x| {
+                    let face = ((x / length) as i32, (y / length) as i32);
+                    if shape.contains(&face) {
+                        '.'
+                    } else {
+                        ' '
+                    }
+                })
+                .collect();
+            rows.push(row.trim_end().to_string());
+        }
+
+        rows.join("\n")
+    }
+
+    #[test]
+    fn test_fold_every_net() {
+        let hexominoes = get_fixed_hexominoes();
+        assert_eq!(216, hexominoes.len());
+
+        let mut nets = HashSet::new();
+        let mut non_nets = HashSet::new();
+
+        for shape in hexominoes.iter() {
+            for length in 1..=3 {
+                let board = draw_board(shape, length);
+                let parts = parse_map(&board);
+
+                let map = match Map::new(&parts, &Cube) {
+                    Ok(map) => map,
+                    Err(error) => {
+                        assert!(matches!(
+                            error,
+                            TopologyError::Fold(FoldError::Overlap { .. })
+                        ));
+                        non_nets.insert(get_canonical_form(shape));
+                        continue;
+                    }
+                };
+                nets.insert(get_canonical_form(shape));
+
+                for (part_id, part) in map.parts.iter().enumerate() {
+                    for dx in 0..length as u32 {
+                        for dy in 0..length as u32 {
+                            for facing in [Facing::Right, Facing::Down, Facing::Left, Facing::Up] {
+                                let state = State {
+                                    position: Coordinate {
+                                        x: part.start.x + dx,
+                                        y: part.start.y + dy,
+                                    },
+                                    facing,
+                                    current_map_part_id: part_id,
+                                };
+
+                                let mut current = state;
+
+                                for step in 1..=4 * length {
+                                    current = move_across_map(&map, current, 1);
+                                    assert_eq!(
+                                        step == 4 * length,
+                                        current == state,
+                                        "Walking around the cube from {:?} on\n{}",
+                                        state,
+                                        board
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        assert_eq!(11, nets.len());
+        assert_eq!(24, non_nets.len());
+    }
+
+    #[test]
+    fn test_reject_non_cube_nets() {
+        let parts = parse_map("...\n..");
+        assert_eq!(
+            Err(TopologyError::Fold(FoldError::FaceCount(5))),
+            Map::new(&parts, &Cube).map(|_| ())
+        );
+
+        let parts = parse_map("..\n..\n..");
+        assert_eq!(
+            Err(TopologyError::Fold(FoldError::Overlap {
+                face: 3,
+                other: 2
+            })),
+            Map::new(&parts, &Cube).map(|_| ())
+        );
+    }
+}
@@ -1,7 +1,13 @@
+mod cube;
+mod solid;
+mod topology;
+mod trace;
+
 use nalgebra::DMatrix;
-use petgraph::algo::isomorphism::is_isomorphic;
-use petgraph::graph::UnGraph;
+use solid::FoldedCube;
 use std::{collections::HashMap, env, fmt::Display, fs};
+use topology::{Cube, KleinBottle, ProjectivePlane, Topology, TopologyError, Torus};
+use trace::{render_faces, render_trace, walk_with_trace};
 
 type Ordinate = u32;
 type Distance = u32;
@@ -76,6 +82,21 @@ enum Flip {
 }
 
 impl Map {
+    fn new(parts: &[MapPart], topology: &impl Topology) -> Result<Map, TopologyError> {
+        for part in parts {
+            log::debug!("Start: {:?}", part.start);
+            log::debug!("{}", part.map);
+        }
+
+        let (starts, net) = create_net(parts);
+        let connections = topology.connect(&starts, &net)?;
+
+        Ok(Map {
+            parts: parts.to_vec(),
+            connections,
+        })
+    }
+
     fn get_part(&self, part_id: usize) -> MapPart {
         self.parts[part_id].clone()
     }
@@ -173,9 +194,21 @@ impl Map {
         log::trace!("Flipped: {:?}", flipped);
         log::trace!("(dx, dy): ({}, {})", dx, dy);
 
-        let transformed = Coordinate {
-            x: (flipped.x as i32 + dx as i32).try_into().unwrap(),
-            y: (flipped.y as i32 + dy as i32).try_into().unwrap(),
+        let translated = Coordinate {
+            x: (flipped.x as i32 + dx).try_into().unwrap(),
+            y: (flipped.y as i32 + dy).try_into().unwrap(),
+        };
+
+        let transformed = match (connection.reversed, new_edge) {
+            (false, _) => translated,
+            (true, Edge::Top | Edge::Bottom) => Coordinate {
+                x: 2 * new_start.x + new_board.get_width() - 1 - translated.x,
+                ..translated
+            },
+            (true, Edge::Left | Edge::Right) => Coordinate {
+                y: 2 * new_start.y + new_board.get_height() - 1 - translated.y,
+                ..translated
+            },
         };
 
         State {
@@ -219,7 +252,7 @@ impl Rectangular for MapPart {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum Edge {
     Top,
     Right,
@@ -227,10 +260,12 @@ enum Edge {
     Left,
 }
 
+// `reversed` glues the edges together the other way round, as on a Klein bottle.
 #[derive(Debug, PartialEq, Clone)]
 struct Connection {
     part_id: usize,
     edge: Edge,
+    reversed: bool,
 }
 
 type MapPartConnection = HashMap<Facing, Connection>;
@@ -241,7 +276,7 @@ enum Turn {
     Clockwise,
 }
 
-#[derive(Hash, Eq, Debug, PartialEq, Copy, Clone)]
+#[derive(Hash, Eq, Debug, PartialEq, PartialOrd, Ord, Copy, Clone)]
 enum Facing {
     Right,
     Down,
@@ -269,6 +304,10 @@ fn load_input() -> String {
     fs::read_to_string(args.get(1).unwrap()).expect("Should have been able to read the file")
 }
 
+fn load_output_format() -> Option<String> {
+    env::args().nth(2)
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 enum Token {
     Blank,
@@ -286,6 +325,32 @@ impl Display for Token {
     }
 }
 
+fn gcd(a: usize, b: usize) -> usize {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+// Every face starts and ends on a multiple of the part length, so it divides every
+// index where a row or column switches between blank and non-blank.
+fn get_part_length(tokenised: &[Vec<Token>]) -> usize {
+    let boundaries = |line: Vec<bool>| {
+        let length = line.len();
+        let mut boundaries: Vec<usize> = (1..length).filter(|&i| line[i - 1] != line[i]).collect();
+        boundaries.push(length);
+        boundaries
+    };
+
+    let ncols = tokenised[0].len();
+    let rows = tokenised
+        .iter()
+        .map(|row| row.iter().map(|&token| token == Token::Blank).collect());
+    let columns = (0..ncols).map(|x| tokenised.iter().map(|row| row[x] == Token::Blank).collect());
+
+    rows.chain(columns).flat_map(boundaries).fold(0, gcd)
+}
+
 fn parse_map(input: &str) -> Vec<MapPart> {
     let mut tokenised: Vec<Vec<Token>> = input
         .split('\n')
@@ -308,18 +373,13 @@ fn parse_map(input: &str) -> Vec<MapPart> {
         .into_iter()
         .map(|mut row| {
             let diff = ncols - row.len();
-            let padding = std::iter::repeat(Token::Blank).take(diff);
+            let padding = std::iter::repeat_n(Token::Blank, diff);
             row.extend(padding);
             row
         })
         .collect();
 
-    // All cube nets have a single square with at some point, this is our part length.
-    let part_length = tokenised
-        .iter()
-        .map(|row| row.iter().filter(|&&token| token != Token::Blank).count())
-        .min()
-        .unwrap();
+    let part_length = get_part_length(&tokenised);
 
     let flat: Vec<Token> = tokenised.into_iter().flatten().collect();
 
@@ -463,61 +523,15 @@ fn create_net(parts: &[MapPart]) -> (Vec<Coordinate>, Net) {
     (starts, net)
 }
 
-fn create_flat_map(parts: &[MapPart]) -> Map {
-    let (starts, net) = create_net(parts);
+fn is_at_edge(part: &MapPart, state: &State) -> bool {
+    let start = part.get_start();
+    let position = state.position;
 
-    let mut connections = Vec::new();
-    for Coordinate { x, y } in starts {
-        let col: Vec<u8> = net
-            .column(x as usize)
-            .into_iter()
-            .cloned()
-            .flatten()
-            .collect();
-        let row: Vec<u8> = net.row(y as usize).into_iter().cloned().flatten().collect();
-
-        log::debug!("Row: {:?}, Col: {:?}", row, col);
-
-        fn modulo(a: i8, b: i8) -> i8 {
-            ((a % b) + b) % b
-        }
-
-        let get_wrap_part = |ord, vec: &[u8]| vec[(modulo(ord, vec.len() as i8)) as usize] as usize;
-
-        let val = net[(y as usize, x as usize)].unwrap();
-        let col_index = col.iter().position(|&r| r == val).unwrap() as i8;
-        let row_index = row.iter().position(|&r| r == val).unwrap() as i8;
-
-        let up = Connection {
-            part_id: get_wrap_part(col_index - 1, &col),
-            edge: Edge::Bottom,
-        };
-        let down = Connection {
-            part_id: get_wrap_part(col_index + 1, &col),
-            edge: Edge::Top,
-        };
-        let right = Connection {
-            part_id: get_wrap_part(row_index + 1, &row),
-            edge: Edge::Left,
-        };
-        let left = Connection {
-            part_id: get_wrap_part(row_index - 1, &row),
-            edge: Edge::Right,
-        };
-
-        connections.push(HashMap::from([
-            (Facing::Up, up),
-            (Facing::Right, right),
-            (Facing::Down, down),
-            (Facing::Left, left),
-        ]));
-    }
-    //
-    log::debug!("Connections: {:#?}", connections);
-
-    Map {
-        parts: parts.to_vec(),
-        connections,
+    match state.facing {
+        Facing::Up => position.y == start.y,
+        Facing::Right => position.x == start.x + part.get_width() - 1,
+        Facing::Down => position.y == start.y + part.get_height() - 1,
+        Facing::Left => position.x == start.x,
     }
 }
 
@@ -531,67 +545,19 @@ fn move_across_map(map: &Map, state: State, distance: Distance) -> State {
         let facing = state.facing;
         let position = state.position;
 
-        let current_start = current_map_part.get_start();
-
-        let potential_state = match facing {
-            Facing::Up => {
-                let is_at_top_edge = position.y == current_start.y;
-                if is_at_top_edge {
-                    map.move_part(state)
-                } else {
-                    State {
-                        position: Coordinate {
-                            y: position.y - 1,
-                            ..position
-                        },
-                        ..state
-                    }
-                }
-            }
-            Facing::Right => {
-                let is_at_right_edge =
-                    position.x == current_start.x + current_map_part.get_width() - 1;
-                if is_at_right_edge {
-                    map.move_part(state)
-                } else {
-                    State {
-                        position: Coordinate {
-                            x: position.x + 1,
-                            ..position
-                        },
-                        ..state
-                    }
-                }
-            }
-            Facing::Down => {
-                let is_at_bottom_edge =
-                    position.y == current_start.y + current_map_part.get_height() - 1;
-                if is_at_bottom_edge {
-                    map.move_part(state)
-                } else {
-                    State {
-                        position: Coordinate {
-                            y: position.y + 1,
-                            ..position
-                        },
-                        ..state
-                    }
-                }
-            }
-            Facing::Left => {
-                let is_at_left_edge = position.x == current_start.x;
-                if is_at_left_edge {
-                    map.move_part(state)
-                } else {
-                    State {
-                        position: Coordinate {
-                            x: position.x - 1,
-                            ..position
-                        },
-                        ..state
-                    }
-                }
-            }
+        let potential_state = if is_at_edge(&current_map_part, &state) {
+            map.move_part(state)
+        } else {
+            let Coordinate { x, y } = position;
+
+            let position = match facing {
+                Facing::Up => Coordinate { x, y: y - 1 },
+                Facing::Right => Coordinate { x: x + 1, y },
+                Facing::Down => Coordinate { x, y: y + 1 },
+                Facing::Left => Coordinate { x: x - 1, y },
+            };
+
+            State { position, ..state }
         };
 
         let current_part = map.get_part(potential_state.current_map_part_id);
@@ -640,12 +606,16 @@ fn follow_step(board_map: &Map, state: State, step: &Step) -> State {
     }
 }
 
-fn walk(board_map: &Map, path: &[Step]) -> State {
-    let initial_state = State {
+fn initial_state(board_map: &Map) -> State {
+    State {
         position: board_map.get_part(0).start,
         facing: Facing::Right,
         current_map_part_id: 0,
-    };
+    }
+}
+
+fn walk(board_map: &Map, path: &[Step]) -> State {
+    let initial_state = initial_state(board_map);
 
     log::debug!("Initial State: {:?}", initial_state);
 
@@ -662,545 +632,66 @@ fn determine_password(state: &State) -> Ordinate {
     1000 * state.position.y + 4 * state.position.x + state.facing as Ordinate
 }
 
-fn main() {
+fn main() -> Result<(), TopologyError> {
     env_logger::init();
     let input = load_input();
     let (map_parts, path) = parse_board_map(&input);
 
-    let flat_map = create_flat_map(&map_parts);
-    let final_state = walk(&flat_map, &path);
-    let final_password = determine_password(&final_state);
-    println!("{}", final_password);
-
-    let cube_map = create_cube_map(&map_parts);
-    let part_2_state = walk(&cube_map, &path);
-    let part_2_password = determine_password(&part_2_state);
-    println!("{}", part_2_password);
-}
-
-fn create_graph(starts: &[Coordinate], net: Net) -> UnGraph<(), (), u8> {
-    let mut edges = Vec::new();
-
-    for start in starts {
-        let (x, y) = (start.x.try_into().unwrap(), start.y.try_into().unwrap());
-        let face_id = net[(y, x)].unwrap();
-
-        const NODES_PER_FACE: u8 = 4;
-
-        const TOP_EDGE_INDEX: u8 = 0;
-        const RIGHT_EDGE_INDEX: u8 = 1;
-        const BOTTOM_EDGE_INDEX: u8 = 2;
-        const LEFT_EDGE_INDEX: u8 = 3;
-
-        let top_node = NODES_PER_FACE * face_id + TOP_EDGE_INDEX;
-        let right_node = NODES_PER_FACE * face_id + RIGHT_EDGE_INDEX;
-        let bottom_node = NODES_PER_FACE * face_id + BOTTOM_EDGE_INDEX;
-        let left_node = NODES_PER_FACE * face_id + LEFT_EDGE_INDEX;
-
-        edges.push((top_node, right_node));
-        edges.push((right_node, bottom_node));
-        edges.push((bottom_node, left_node));
-        edges.push((left_node, top_node));
-
-        if y > 0 {
-            if let Some(above_face) = net[(y - 1, x)] {
-                let above_face_bottom_edge = NODES_PER_FACE * above_face + BOTTOM_EDGE_INDEX;
-                edges.push((top_node, above_face_bottom_edge));
+    if let Some(format) = load_output_format() {
+        let board = input.split("\n\n").next().unwrap();
+
+        match format.as_str() {
+            "--trace" => {
+                let flat_map = Map::new(&map_parts, &Torus)?;
+                let cube_map = Map::new(&map_parts, &Cube)?;
+                println!(
+                    "{}\n",
+                    render_trace(board, &walk_with_trace(&flat_map, &path))
+                );
+                println!(
+                    "{}",
+                    render_trace(board, &walk_with_trace(&cube_map, &path))
+                );
             }
-        }
-
-        if x < net.shape().1 - 2 {
-            if let Some(right_face) = net[(y, x + 1)] {
-                let right_face_left_edge = NODES_PER_FACE * right_face + LEFT_EDGE_INDEX;
-                edges.push((right_node, right_face_left_edge));
+            "--faces" => {
+                let cube_map = Map::new(&map_parts, &Cube)?;
+                print!(
+                    "{}",
+                    render_faces(&cube_map, &walk_with_trace(&cube_map, &path))
+                );
             }
-        }
-
-        if y < net.shape().0 - 2 {
-            if let Some(bottom_face) = net[(y + 1, x)] {
-                let bottom_face_top_edge = NODES_PER_FACE * bottom_face + TOP_EDGE_INDEX;
-                edges.push((bottom_node, bottom_face_top_edge));
+            "--cross-check" => {
+                let cube_map = Map::new(&map_parts, &Cube)?;
+                let cube = FoldedCube::new(&map_parts)?;
+                let expected = determine_password(&walk(&cube_map, &path));
+                let actual = determine_password(&cube.walk(&initial_state(&cube_map), &path));
+                println!("Connection table: {}\nSolid cube: {}", expected, actual);
+                assert_eq!(expected, actual, "The cube engines disagree");
             }
-        }
-
-        if x > 0 {
-            if let Some(left_face) = net[(y, x - 1)] {
-                let left_face_right_edge = NODES_PER_FACE * left_face + RIGHT_EDGE_INDEX;
-                edges.push((left_node, left_face_right_edge));
+            "--klein-bottle" => {
+                let map = Map::new(&map_parts, &KleinBottle)?;
+                println!("{}", determine_password(&walk(&map, &path)));
             }
+            "--projective-plane" => {
+                let map = Map::new(&map_parts, &ProjectivePlane)?;
+                println!("{}", determine_password(&walk(&map, &path)));
+            }
+            _ => panic!("Unknown output format {}", format),
         }
+        return Ok(());
     }
 
-    let graph = UnGraph::<(), (), u8>::from_edges(&edges);
-
-    log::debug!("{:?}", graph);
-
-    graph
-}
-
-fn create_cube_map(map_parts: &[MapPart]) -> Map {
-    for part in map_parts {
-        log::debug!("Start: {:?}", part.start);
-        log::debug!("{}", part.map);
-    }
-
-    let (starts, net) = create_net(map_parts);
-    let graph = create_graph(&starts, net);
-    let connections = get_connections(graph);
+    let flat_map = Map::new(&map_parts, &Torus)?;
+    let final_state = walk(&flat_map, &path);
+    let final_password = determine_password(&final_state);
+    println!("{}", final_password);
 
-    Map {
-        parts: map_parts.to_vec(),
-        connections,
-    }
-}
+    let cube_map = Map::new(&map_parts, &Cube)?;
+    let part_2_state = walk(&cube_map, &path);
+    let part_2_password = determine_password(&part_2_state);
+    println!("{}", part_2_password);
 
-fn get_connections(graph: UnGraph<(), (), u8>) -> Vec<MapPartConnection> {
-    let graph_to_connections_map: Vec<(UnGraph<(), (), u8>, Vec<MapPartConnection>)> = vec![
-        (
-            UnGraph::<(), (), u8>::from_edges(&[
-                (0, 1),
-                (1, 2),
-                (2, 3),
-                (3, 0),
-                (2, 8),
-                (4, 5),
-                (5, 6),
-                (6, 7),
-                (7, 4),
-                (7, 1),
-                (8, 9),
-                (9, 10),
-                (10, 11),
-                (11, 8),
-                (8, 2),
-                (10, 16),
-                (12, 13),
-                (13, 14),
-                (14, 15),
-                (15, 12),
-                (13, 19),
-                (16, 17),
-                (17, 18),
-                (18, 19),
-                (19, 16),
-                (16, 10),
-                (19, 13),
-                (20, 21),
-                (21, 22),
-                (22, 23),
-                (23, 20),
-                (20, 14),
-            ]),
-            vec![
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 5,
-                            edge: Edge::Left,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 1,
-                            edge: Edge::Left,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 2,
-                            edge: Edge::Top,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 3,
-                            edge: Edge::Left,
-                        },
-                    ),
-                ]),
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 5,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 4,
-                            edge: Edge::Right,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 2,
-                            edge: Edge::Right,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 0,
-                            edge: Edge::Right,
-                        },
-                    ),
-                ]),
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 0,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 1,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 4,
-                            edge: Edge::Top,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 3,
-                            edge: Edge::Top,
-                        },
-                    ),
-                ]),
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 2,
-                            edge: Edge::Left,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 4,
-                            edge: Edge::Left,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 5,
-                            edge: Edge::Top,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 0,
-                            edge: Edge::Left,
-                        },
-                    ),
-                ]),
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 2,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 1,
-                            edge: Edge::Right,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 5,
-                            edge: Edge::Right,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 3,
-                            edge: Edge::Right,
-                        },
-                    ),
-                ]),
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 3,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 4,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 1,
-                            edge: Edge::Top,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 0,
-                            edge: Edge::Top,
-                        },
-                    ),
-                ]),
-            ],
-        ),
-        (
-            UnGraph::<(), (), u8>::from_edges(&[
-                (0, 1),
-                (1, 2),
-                (2, 3),
-                (3, 0),
-                (2, 12),
-                (4, 5),
-                (5, 6),
-                (6, 7),
-                (7, 4),
-                (5, 11),
-                (8, 9),
-                (9, 10),
-                (10, 11),
-                (11, 8),
-                (9, 15),
-                (11, 5),
-                (12, 13),
-                (13, 14),
-                (14, 15),
-                (15, 12),
-                (12, 2),
-                (15, 9),
-                (16, 17),
-                (17, 18),
-                (18, 19),
-                (19, 16),
-                (16, 14),
-                (20, 21),
-                (21, 22),
-                (22, 23),
-                (23, 20),
-                (23, 17),
-            ]),
-            vec![
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 1,
-                            edge: Edge::Top,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 5,
-                            edge: Edge::Right,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 3,
-                            edge: Edge::Top,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 2,
-                            edge: Edge::Top,
-                        },
-                    ),
-                ]),
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 0,
-                            edge: Edge::Top,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 2,
-                            edge: Edge::Left,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 4,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 5,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                ]),
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 0,
-                            edge: Edge::Left,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 3,
-                            edge: Edge::Left,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 4,
-                            edge: Edge::Left,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 1,
-                            edge: Edge::Right,
-                        },
-                    ),
-                ]),
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 0,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 5,
-                            edge: Edge::Top,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 4,
-                            edge: Edge::Top,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 2,
-                            edge: Edge::Right,
-                        },
-                    ),
-                ]),
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 3,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 5,
-                            edge: Edge::Left,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 1,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 2,
-                            edge: Edge::Bottom,
-                        },
-                    ),
-                ]),
-                HashMap::from([
-                    (
-                        Facing::Up,
-                        Connection {
-                            part_id: 3,
-                            edge: Edge::Right,
-                        },
-                    ),
-                    (
-                        Facing::Down,
-                        Connection {
-                            part_id: 1,
-                            edge: Edge::Left,
-                        },
-                    ),
-                    (
-                        Facing::Left,
-                        Connection {
-                            part_id: 4,
-                            edge: Edge::Right,
-                        },
-                    ),
-                    (
-                        Facing::Right,
-                        Connection {
-                            part_id: 0,
-                            edge: Edge::Right,
-                        },
-                    ),
-                ]),
-            ],
-        ),
-    ];
-
-    match graph_to_connections_map
-        .into_iter()
-        .find(|(other_graph, _)| is_isomorphic(&graph, other_graph))
-    {
-        Some((_, connections)) => connections.to_vec(),
-        None => todo!(),
-    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -1211,8 +702,8 @@ mod tests {
 
     use crate::Turn::{AntiClockwise, Clockwise};
     use crate::{
-        determine_password, parse_board_map, single_90_clockwise_rotation, walk, Connection,
-        Coordinate, Edge, Facing, Map, MapPart, Path, State, Step, Token,
+        determine_password, parse_board_map, single_90_clockwise_rotation, topology::Cube, walk,
+        Connection, Coordinate, Edge, Facing, Map, MapPart, Path, State, Step, Token,
     };
 
     fn init() {
@@ -1306,6 +797,7 @@ mod tests {
                     Connection {
                         part_id: 4,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1313,6 +805,7 @@ mod tests {
                     Connection {
                         part_id: 0,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1320,6 +813,7 @@ mod tests {
                     Connection {
                         part_id: 3,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1327,6 +821,7 @@ mod tests {
                     Connection {
                         part_id: 0,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1336,6 +831,7 @@ mod tests {
                     Connection {
                         part_id: 1,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1343,6 +839,7 @@ mod tests {
                     Connection {
                         part_id: 2,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1350,6 +847,7 @@ mod tests {
                     Connection {
                         part_id: 1,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1357,6 +855,7 @@ mod tests {
                     Connection {
                         part_id: 3,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1366,6 +865,7 @@ mod tests {
                     Connection {
                         part_id: 2,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1373,6 +873,7 @@ mod tests {
                     Connection {
                         part_id: 3,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1380,6 +881,7 @@ mod tests {
                     Connection {
                         part_id: 2,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1387,6 +889,7 @@ mod tests {
                     Connection {
                         part_id: 1,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1396,6 +899,7 @@ mod tests {
                     Connection {
                         part_id: 0,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1403,6 +907,7 @@ mod tests {
                     Connection {
                         part_id: 1,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1410,6 +915,7 @@ mod tests {
                     Connection {
                         part_id: 4,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1417,6 +923,7 @@ mod tests {
                     Connection {
                         part_id: 2,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1426,6 +933,7 @@ mod tests {
                     Connection {
                         part_id: 3,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1433,6 +941,7 @@ mod tests {
                     Connection {
                         part_id: 5,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1440,6 +949,7 @@ mod tests {
                     Connection {
                         part_id: 0,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1447,6 +957,7 @@ mod tests {
                     Connection {
                         part_id: 5,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1456,6 +967,7 @@ mod tests {
                     Connection {
                         part_id: 5,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1463,6 +975,7 @@ mod tests {
                     Connection {
                         part_id: 5,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1470,6 +983,7 @@ mod tests {
                     Connection {
                         part_id: 4,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1477,6 +991,7 @@ mod tests {
                     Connection {
                         part_id: 4,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1497,6 +1012,7 @@ mod tests {
                     Connection {
                         part_id: 1,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1504,6 +1020,7 @@ mod tests {
                     Connection {
                         part_id: 5,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1511,6 +1028,7 @@ mod tests {
                     Connection {
                         part_id: 3,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1518,6 +1036,7 @@ mod tests {
                     Connection {
                         part_id: 2,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1527,6 +1046,7 @@ mod tests {
                     Connection {
                         part_id: 0,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1534,6 +1054,7 @@ mod tests {
                     Connection {
                         part_id: 2,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1541,6 +1062,7 @@ mod tests {
                     Connection {
                         part_id: 4,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1548,6 +1070,7 @@ mod tests {
                     Connection {
                         part_id: 5,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1557,6 +1080,7 @@ mod tests {
                     Connection {
                         part_id: 0,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1564,6 +1088,7 @@ mod tests {
                     Connection {
                         part_id: 3,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1571,6 +1096,7 @@ mod tests {
                     Connection {
                         part_id: 4,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1578,6 +1104,7 @@ mod tests {
                     Connection {
                         part_id: 1,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1587,6 +1114,7 @@ mod tests {
                     Connection {
                         part_id: 0,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1594,6 +1122,7 @@ mod tests {
                     Connection {
                         part_id: 5,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1601,6 +1130,7 @@ mod tests {
                     Connection {
                         part_id: 4,
                         edge: Edge::Top,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1608,6 +1138,7 @@ mod tests {
                     Connection {
                         part_id: 2,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1617,6 +1148,7 @@ mod tests {
                     Connection {
                         part_id: 3,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1624,6 +1156,7 @@ mod tests {
                     Connection {
                         part_id: 5,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1631,6 +1164,7 @@ mod tests {
                     Connection {
                         part_id: 1,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1638,6 +1172,7 @@ mod tests {
                     Connection {
                         part_id: 2,
                         edge: Edge::Bottom,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1647,6 +1182,7 @@ mod tests {
                     Connection {
                         part_id: 3,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1654,6 +1190,7 @@ mod tests {
                     Connection {
                         part_id: 1,
                         edge: Edge::Left,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1661,6 +1198,7 @@ mod tests {
                     Connection {
                         part_id: 4,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
                 (
@@ -1668,6 +1206,7 @@ mod tests {
                     Connection {
                         part_id: 0,
                         edge: Edge::Right,
+                        reversed: false,
                     },
                 ),
             ]),
@@ -1713,6 +1252,15 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_create_cube_map() {
+        init();
+        let (parts, _) = parse_board_map(include_str!("../test.txt"));
+        let (expected, _) = get_part_2_test_input();
+        let actual = Map::new(&parts, &Cube).unwrap();
+        assert_eq!(expected.connections, actual.connections);
+    }
+
     #[test]
     fn test_determine_password() {
         let input = State {
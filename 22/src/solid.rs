@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+
+use crate::{
+    create_net,
+    cube::{fold_frames, FoldError, Frame},
+    Coordinate, Distance, MapPart, Ordinate, Rectangular, State, Step, Token, Turn,
+};
+
+type Vector = Vector3<i32>;
+
+// Somewhere on the surface of the cube, heading along `direction`. Positions are
+// tile centres scaled by two so they stay on the integer lattice: a cube with side
+// length L spans -L..=L on every axis.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Pose {
+    position: Vector,
+    normal: Vector,
+    direction: Vector,
+}
+
+#[derive(Debug)]
+struct Tile {
+    part_id: usize,
+    coordinate: Coordinate,
+    wall: bool,
+}
+
+// The board folded up into an actual cube. No face of a cube shares a tile centre with
+// another, so tiles can be looked up by position alone.
+pub struct FoldedCube {
+    frames: Vec<Frame>,
+    starts: Vec<Coordinate>,
+    tiles: HashMap<Vector, Tile>,
+    length: i32,
+}
+
+impl FoldedCube {
+    pub fn new(parts: &[MapPart]) -> Result<FoldedCube, FoldError> {
+        let (net_starts, net) = create_net(parts);
+        let frames = fold_frames(&net_starts, &net)?;
+        let length = parts[0].get_width() as i32;
+
+        let mut tiles = HashMap::new();
+
+        for (part_id, (part, frame)) in parts.iter().zip(frames.iter()).enumerate() {
+            let start = part.get_start();
+
+            for y in 0..length {
+                for x in 0..length {
+                    let position = frame.normal * length
+                        + frame.right * (2 * x + 1 - length)
+                        + frame.down * (2 * y + 1 - length);
+
+                    let tile = Tile {
+                        part_id,
+                        coordinate: Coordinate {
+                            x: start.x + x as Ordinate,
+                            y: start.y + y as Ordinate,
+                        },
+                        wall: part.map[(y as usize, x as usize)] == Token::SolidWall,
+                    };
+
+                    tiles.insert(position, tile);
+                }
+            }
+        }
+
+        Ok(FoldedCube {
+            frames,
+            starts: parts.iter().map(|part| part.get_start()).collect(),
+            tiles,
+            length,
+        })
+    }
+
+    pub fn pose(&self, state: &State) -> Pose {
+        let frame = &self.frames[state.current_map_part_id];
+        let start = self.starts[state.current_map_part_id];
+        let x = (state.position.x - start.x) as i32;
+        let y = (state.position.y - start.y) as i32;
+
+        Pose {
+            position: frame.normal * self.length
+                + frame.right * (2 * x + 1 - self.length)
+                + frame.down * (2 * y + 1 - self.length),
+            normal: frame.normal,
+            direction: frame.direction(state.facing),
+        }
+    }
+
+    // Projects a pose back onto the board it was folded from.
+    pub fn state(&self, pose: &Pose) -> State {
+        let tile = &self.tiles[&pose.position];
+
+        State {
+            position: tile.coordinate,
+            facing: self.frames[tile.part_id].facing(pose.direction),
+            current_map_part_id: tile.part_id,
+        }
+    }
+
+    fn step(&self, pose: &Pose) -> Pose {
+        let next = pose.position + pose.direction * 2;
+
+        // Stepping off the face tips over the edge onto the face it was heading towards.
+        if next.dot(&pose.direction) > self.length {
+            Pose {
+                position: pose.position + pose.direction - pose.normal,
+                normal: pose.direction,
+                direction: -pose.normal,
+            }
+        } else {
+            Pose {
+                position: next,
+                ..*pose
+            }
+        }
+    }
+
+    fn move_across(&self, pose: Pose, distance: Distance) -> Pose {
+        let mut pose = pose;
+
+        for _ in 0..distance {
+            let next = self.step(&pose);
+
+            if self.tiles[&next.position].wall {
+                break;
+            }
+
+            pose = next;
+        }
+
+        pose
+    }
+
+    pub fn follow_step(&self, pose: Pose, step: &Step) -> Pose {
+        match step {
+            Step::Move(distance) => self.move_across(pose, *distance),
+            // Looking down at the face from outside the cube.
+            Step::Turn(Turn::Clockwise) => Pose {
+                direction: pose.direction.cross(&pose.normal),
+                ..pose
+            },
+            Step::Turn(Turn::AntiClockwise) => Pose {
+                direction: pose.normal.cross(&pose.direction),
+                ..pose
+            },
+        }
+    }
+
+    pub fn walk(&self, initial_state: &State, path: &[Step]) -> State {
+        let pose = path.iter().fold(self.pose(initial_state), |pose, step| {
+            self.follow_step(pose, step)
+        });
+
+        self.state(&pose)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        follow_step, initial_state, parse_board_map, parse_map, topology::Cube, walk, Map, Step,
+        Turn,
+    };
+
+    use super::FoldedCube;
+
+    const NETS: [&str; 11] = [
+        ".\n....\n.",
+        ".\n....\n .",
+        ".\n....\n  .",
+        ".\n....\n   .",
+        " .\n....\n .",
+        " .\n....\n  .",
+        "..\n ...\n .",
+        "..\n ...\n  .",
+        "..\n ...\n   .",
+        "..\n ..\n  ..",
+        "...\n  ...",
+    ];
+
+    // Scatters walls over the faces in an irregular but fixed pattern.
+    fn draw_board(net: &str, length: usize) -> String {
+        net.lines()
+            .flat_map(|row| std::iter::repeat_n(row, length))
+            .enumerate()
+            .map(|(y, row)| {
+                row.chars()
+                    .flat_map(|face| std::iter::repeat_n(face, length))
+                    .enumerate()
+                    .map(|(x, tile)| match tile {
+                        '.' if (3 * x + y * y) % 7 == 0 => '#',
+                        tile => tile,
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_walk_matches_connection_table() {
+        let (parts, path) = parse_board_map(include_str!("../test.txt"));
+        let map = Map::new(&parts, &Cube).unwrap();
+        let cube = FoldedCube::new(&parts).unwrap();
+        let initial_state = initial_state(&map);
+
+        assert_eq!(walk(&map, &path), cube.walk(&initial_state, &path));
+
+        for end in 0..path.len() {
+            assert_eq!(
+                walk(&map, &path[..end]),
+                cube.walk(&initial_state, &path[..end])
+            );
+        }
+    }
+
+    #[test]
+    fn test_walks_on_every_net() {
+        for net in NETS {
+            for length in 1..=4 {
+                let board = draw_board(net, length);
+                let parts = parse_map(&board);
+                let map = Map::new(&parts, &Cube).unwrap();
+                let cube = FoldedCube::new(&parts).unwrap();
+
+                let mut state = initial_state(&map);
+                let mut pose = cube.pose(&state);
+
+                // Turns clockwise a quarter more than back every five steps, so the walk
+                // heads every way in turn, with moves of every length up to five faces.
+                for i in 0..200 {
+                    let step = match i % 5 {
+                        0 | 2 => Step::Turn(Turn::Clockwise),
+                        4 => Step::Turn(Turn::AntiClockwise),
+                        _ => Step::Move((i * 7 % (5 * length)) as u32),
+                    };
+
+                    state = follow_step(&map, state, &step);
+                    pose = cube.follow_step(pose, &step);
+
+                    assert_eq!(state, cube.state(&pose), "After {:?} on\n{}", step, board);
+                }
+            }
+        }
+    }
+}
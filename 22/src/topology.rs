@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+
+use crate::{
+    cube::{fold_cube, FoldError},
+    Connection, Coordinate, Edge, Facing, MapPartConnection, Net,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TopologyError {
+    Fold(FoldError),
+    // A row or column of the board with no parts for another to be glued to.
+    EmptyRow(usize),
+    EmptyColumn(usize),
+}
+
+impl From<FoldError> for TopologyError {
+    fn from(error: FoldError) -> Self {
+        TopologyError::Fold(error)
+    }
+}
+
+// Decides which edge of which part the walker arrives through after leaving a part.
+pub trait Topology {
+    fn connect(
+        &self,
+        starts: &[Coordinate],
+        net: &Net,
+    ) -> Result<Vec<MapPartConnection>, TopologyError>;
+}
+
+// Wraps around within each row and column of the board.
+pub struct Torus;
+
+pub struct Cube;
+
+// A board whose rows wrap around onto their mirrored rows upside down.
+pub struct KleinBottle;
+
+// A board whose rows and columns both wrap around onto their mirrored ones upside down.
+pub struct ProjectivePlane;
+
+fn connection(part_id: u8, edge: Edge, reversed: bool) -> Connection {
+    Connection {
+        part_id: part_id as usize,
+        edge,
+        reversed,
+    }
+}
+
+impl Topology for Torus {
+    fn connect(
+        &self,
+        starts: &[Coordinate],
+        net: &Net,
+    ) -> Result<Vec<MapPartConnection>, TopologyError> {
+        let mut connections = Vec::new();
+
+        for &Coordinate { x, y } in starts {
+            let col: Vec<u8> = net
+                .column(x as usize)
+                .into_iter()
+                .cloned()
+                .flatten()
+                .collect();
+            let row: Vec<u8> = net.row(y as usize).into_iter().cloned().flatten().collect();
+
+            log::debug!("Row: {:?}, Col: {:?}", row, col);
+
+            let get_wrap_part = |ord: i8, vec: &[u8]| vec[ord.rem_euclid(vec.len() as i8) as usize];
+
+            let val = net[(y as usize, x as usize)].unwrap();
+            let col_index = col.iter().position(|&r| r == val).unwrap() as i8;
+            let row_index = row.iter().position(|&r| r == val).unwrap() as i8;
+
+            connections.push(HashMap::from([
+                (
+                    Facing::Up,
+                    connection(get_wrap_part(col_index - 1, &col), Edge::Bottom, false),
+                ),
+                (
+                    Facing::Right,
+                    connection(get_wrap_part(row_index + 1, &row), Edge::Left, false),
+                ),
+                (
+                    Facing::Down,
+                    connection(get_wrap_part(col_index + 1, &col), Edge::Top, false),
+                ),
+                (
+                    Facing::Left,
+                    connection(get_wrap_part(row_index - 1, &row), Edge::Right, false),
+                ),
+            ]));
+        }
+
+        log::debug!("Connections: {:#?}", connections);
+
+        Ok(connections)
+    }
+}
+
+impl Topology for Cube {
+    fn connect(
+        &self,
+        starts: &[Coordinate],
+        net: &Net,
+    ) -> Result<Vec<MapPartConnection>, TopologyError> {
+        Ok(fold_cube(starts, net)?)
+    }
+}
+
+// Wraps around within each row and column like the torus, except that leaving either end of a
+// row carries on from the other end of the mirrored row, upside down. With `reverse_vertical`
+// the columns are glued to their mirrored columns the same way.
+fn connect_mirrored(
+    starts: &[Coordinate],
+    net: &Net,
+    reverse_vertical: bool,
+) -> Result<Vec<MapPartConnection>, TopologyError> {
+    let (width, height) = (net.ncols(), net.nrows());
+
+    let row = |y: usize| -> Vec<u8> { net.row(y).iter().flatten().copied().collect() };
+    let column = |x: usize| -> Vec<u8> { net.column(x).iter().flatten().copied().collect() };
+
+    let mirrored_row = |y: usize| {
+        let parts = row(height - 1 - y);
+        match (parts.first(), parts.last()) {
+            (Some(&first), Some(&last)) => Ok((first, last)),
+            _ => Err(TopologyError::EmptyRow(height - 1 - y)),
+        }
+    };
+    let mirrored_column = |x: usize| {
+        let parts = column(width - 1 - x);
+        match (parts.first(), parts.last()) {
+            (Some(&first), Some(&last)) => Ok((first, last)),
+            _ => Err(TopologyError::EmptyColumn(width - 1 - x)),
+        }
+    };
+
+    let connections = starts
+        .iter()
+        .map(|start| {
+            let (x, y) = (start.x as usize, start.y as usize);
+            let id = net[(y, x)].unwrap();
+
+            let row = row(y);
+            let column = column(x);
+            let i = row.iter().position(|&part| part == id).unwrap();
+            let j = column.iter().position(|&part| part == id).unwrap();
+
+            let up = match (j, reverse_vertical) {
+                (0, true) => connection(mirrored_column(x)?.1, Edge::Bottom, true),
+                (0, false) => connection(column[column.len() - 1], Edge::Bottom, false),
+                _ => connection(column[j - 1], Edge::Bottom, false),
+            };
+            let down = match (j == column.len() - 1, reverse_vertical) {
+                (true, true) => connection(mirrored_column(x)?.0, Edge::Top, true),
+                (true, false) => connection(column[0], Edge::Top, false),
+                _ => connection(column[j + 1], Edge::Top, false),
+            };
+            let right = match i == row.len() - 1 {
+                true => connection(mirrored_row(y)?.0, Edge::Left, true),
+                false => connection(row[i + 1], Edge::Left, false),
+            };
+            let left = match i {
+                0 => connection(mirrored_row(y)?.1, Edge::Right, true),
+                _ => connection(row[i - 1], Edge::Right, false),
+            };
+
+            Ok(HashMap::from([
+                (Facing::Up, up),
+                (Facing::Right, right),
+                (Facing::Down, down),
+                (Facing::Left, left),
+            ]))
+        })
+        .collect::<Result<Vec<MapPartConnection>, TopologyError>>()?;
+
+    log::debug!("Connections: {:#?}", connections);
+
+    Ok(connections)
+}
+
+impl Topology for KleinBottle {
+    fn connect(
+        &self,
+        starts: &[Coordinate],
+        net: &Net,
+    ) -> Result<Vec<MapPartConnection>, TopologyError> {
+        connect_mirrored(starts, net, false)
+    }
+}
+
+impl Topology for ProjectivePlane {
+    fn connect(
+        &self,
+        starts: &[Coordinate],
+        net: &Net,
+    ) -> Result<Vec<MapPartConnection>, TopologyError> {
+        connect_mirrored(starts, net, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        determine_password, follow_step, parse_board_map, parse_map, walk, Coordinate, Facing, Map,
+        State, Step,
+    };
+
+    use super::{KleinBottle, ProjectivePlane, Topology, TopologyError};
+
+    fn walk_straight(topology: &impl Topology, start: (u32, u32), facing: Facing) -> Vec<State> {
+        let parts = parse_map("....\n....\n....\n....\n....\n....");
+        let map = Map::new(&parts, topology).unwrap();
+
+        let mut state = State {
+            position: Coordinate {
+                x: start.0,
+                y: start.1,
+            },
+            facing,
+            current_map_part_id: map
+                .parts
+                .iter()
+                .position(|part| {
+                    (part.start.x..part.start.x + 2).contains(&start.0)
+                        && (part.start.y..part.start.y + 2).contains(&start.1)
+                })
+                .unwrap(),
+        };
+
+        (0..12)
+            .map(|_| {
+                state = follow_step(&map, state, &Step::Move(1));
+                state
+            })
+            .collect()
+    }
+
+    fn positions(states: &[State]) -> Vec<(u32, u32)> {
+        states
+            .iter()
+            .map(|state| (state.position.x, state.position.y))
+            .collect()
+    }
+
+    #[test]
+    fn test_klein_bottle() {
+        let states = walk_straight(&KleinBottle, (3, 2), Facing::Right);
+        assert_eq!((4, 2), positions(&states)[0]);
+        assert_eq!((1, 5), positions(&states)[1]);
+        assert_eq!((4, 5), positions(&states)[4]);
+        assert_eq!((3, 2), positions(&states)[7]);
+        assert!(states.iter().all(|state| state.facing == Facing::Right));
+
+        let states = walk_straight(&KleinBottle, (3, 2), Facing::Up);
+        assert_eq!((3, 1), positions(&states)[0]);
+        assert_eq!((3, 6), positions(&states)[1]);
+        assert_eq!((3, 2), positions(&states)[5]);
+        assert!(states.iter().all(|state| state.facing == Facing::Up));
+    }
+
+    #[test]
+    fn test_projective_plane() {
+        let states = walk_straight(&ProjectivePlane, (3, 2), Facing::Left);
+        assert_eq!((1, 2), positions(&states)[1]);
+        assert_eq!((4, 5), positions(&states)[2]);
+        assert_eq!((3, 2), positions(&states)[7]);
+        assert!(states.iter().all(|state| state.facing == Facing::Left));
+
+        let states = walk_straight(&ProjectivePlane, (3, 2), Facing::Down);
+        assert_eq!((3, 6), positions(&states)[3]);
+        assert_eq!((2, 1), positions(&states)[4]);
+        assert_eq!((3, 2), positions(&states)[11]);
+        assert!(states.iter().all(|state| state.facing == Facing::Down));
+    }
+
+    #[test]
+    fn test_cube_net_boards() {
+        let (parts, path) = parse_board_map(include_str!("../test.txt"));
+
+        let map = Map::new(&parts, &KleinBottle).unwrap();
+        assert_eq!(6032, determine_password(&walk(&map, &path)));
+
+        let map = Map::new(&parts, &ProjectivePlane).unwrap();
+        assert_eq!(2036, determine_password(&walk(&map, &path)));
+
+        let state = State {
+            position: Coordinate { x: 9, y: 12 },
+            facing: Facing::Down,
+            current_map_part_id: 4,
+        };
+        let state = follow_step(&map, state, &Step::Move(1));
+        assert_eq!(Coordinate { x: 8, y: 5 }, state.position);
+        assert_eq!(Facing::Down, state.facing);
+    }
+
+    #[test]
+    fn test_empty_mirror() {
+        let parts = parse_map("....    \n....    \n      ..\n      ..");
+
+        assert!(Map::new(&parts, &KleinBottle).is_ok());
+        assert_eq!(
+            Err(TopologyError::EmptyColumn(2)),
+            Map::new(&parts, &ProjectivePlane).map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{
+    follow_step, initial_state, is_at_edge, Edge, Facing, Map, MapPart, Rectangular, State, Step,
+    Token,
+};
+
+pub type Trace = Vec<State>;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Transition {
+    from: usize,
+    facing: Facing,
+    to: usize,
+    edge: Edge,
+}
+
+// Records every tile visited along the path, including the facing after each turn.
+pub fn walk_with_trace(board_map: &Map, path: &[Step]) -> Trace {
+    let mut state = initial_state(board_map);
+    let mut trace = vec![state];
+
+    for step in path.iter() {
+        match step {
+            Step::Move(distance) => {
+                for _ in 0..*distance {
+                    let next = follow_step(board_map, state, &Step::Move(1));
+
+                    if next == state {
+                        break;
+                    }
+
+                    state = next;
+                    trace.push(state);
+                }
+            }
+            Step::Turn(_) => {
+                state = follow_step(board_map, state, step);
+                trace.push(state);
+            }
+        }
+    }
+
+    trace
+}
+
+pub fn get_transitions(board_map: &Map, trace: &[State]) -> Vec<Transition> {
+    trace
+        .windows(2)
+        .filter(|states| {
+            states[0].position != states[1].position
+                && is_at_edge(&board_map.parts[states[0].current_map_part_id], &states[0])
+        })
+        .map(|states| Transition {
+            from: states[0].current_map_part_id,
+            facing: states[0].facing,
+            to: states[1].current_map_part_id,
+            edge: board_map.connections[states[0].current_map_part_id][&states[0].facing].edge,
+        })
+        .collect()
+}
+
+fn facing_glyph(facing: Facing) -> char {
+    match facing {
+        Facing::Right => '>',
+        Facing::Down => 'v',
+        Facing::Left => '<',
+        Facing::Up => '^',
+    }
+}
+
+pub fn render_trace(board: &str, trace: &[State]) -> String {
+    let mut rows: Vec<Vec<char>> = board.lines().map(|row| row.chars().collect()).collect();
+
+    for state in trace {
+        let (x, y) = (state.position.x as usize - 1, state.position.y as usize - 1);
+        rows[y][x] = facing_glyph(state.facing);
+    }
+
+    rows.into_iter()
+        .map(|row| row.into_iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn render_part(part: &MapPart, part_id: usize, trace: &[State]) -> Vec<String> {
+    let start = part.get_start();
+    let mut rows: Vec<Vec<char>> = part
+        .map
+        .row_iter()
+        .map(|row| {
+            row.iter()
+                .map(|token| match token {
+                    Token::SolidWall => '#',
+                    _ => '.',
+                })
+                .collect()
+        })
+        .collect();
+
+    for state in trace
+        .iter()
+        .filter(|state| state.current_map_part_id == part_id)
+    {
+        let (x, y) = (state.position.x - start.x, state.position.y - start.y);
+        rows[y as usize][x as usize] = facing_glyph(state.facing);
+    }
+
+    rows.into_iter()
+        .map(|row| row.into_iter().collect())
+        .collect()
+}
+
+// Draws each face of the map on its own with the path over it, followed by every edge
+// the path left that face by and how often it did so.
+pub fn render_faces(board_map: &Map, trace: &[State]) -> String {
+    let mut counts: BTreeMap<Transition, usize> = BTreeMap::new();
+    for transition in get_transitions(board_map, trace) {
+        *counts.entry(transition).or_default() += 1;
+    }
+
+    let mut output = String::new();
+
+    for (part_id, part) in board_map.parts.iter().enumerate() {
+        let start = part.get_start();
+        writeln!(
+            output,
+            "== Face {} at ({}, {}) ==",
+            part_id, start.x, start.y
+        )
+        .unwrap();
+
+        for row in render_part(part, part_id, trace) {
+            writeln!(output, "{}", row).unwrap();
+        }
+
+        for (transition, count) in counts.iter().filter(|(t, _)| t.from == part_id) {
+            writeln!(
+                output,
+                "{:?} -> face {} through its {:?} edge: {}",
+                transition.facing, transition.to, transition.edge, count
+            )
+            .unwrap();
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse_board_map,
+        topology::{Cube, Torus},
+        walk, Edge, Facing, Map,
+    };
+
+    use super::{get_transitions, render_faces, render_trace, walk_with_trace, Transition};
+
+    #[test]
+    fn test_render_trace() {
+        let input = include_str!("../test.txt");
+        let board = input.split("\n\n").next().unwrap();
+        let (parts, path) = parse_board_map(input);
+
+        let map = Map::new(&parts, &Torus).unwrap();
+        let trace = walk_with_trace(&map, &path);
+        assert_eq!(Some(&walk(&map, &path)), trace.last());
+
+        let expected = "        >>v#
+        .#v.
+        #.v.
+        ..v.
+...#...v..v#
+>>>v...>#.>>
+..#v...#....
+...>>>>v..#.
+        ...#....
+        .....#..
+        .#......
+        ......#.";
+        assert_eq!(expected, render_trace(board, &trace));
+
+        let map = Map::new(&parts, &Cube).unwrap();
+        let trace = walk_with_trace(&map, &path);
+        assert_eq!(Some(&walk(&map, &path)), trace.last());
+
+        let expected = "        >>v#
+        .#v.
+        #.v.
+        ..v.
+...#..^...v#
+.>>>>>^.#.>>
+.^#....#....
+.^........#.
+        ...#..v.
+        .....#v.
+        .#v<<<<.
+        ..v...#.";
+        assert_eq!(expected, render_trace(board, &trace));
+    }
+
+    #[test]
+    fn test_render_faces() {
+        let (parts, path) = parse_board_map(include_str!("../test.txt"));
+        let map = Map::new(&parts, &Cube).unwrap();
+        let trace = walk_with_trace(&map, &path);
+
+        assert_eq!(
+            vec![
+                Transition {
+                    from: 0,
+                    facing: Facing::Down,
+                    to: 3,
+                    edge: Edge::Top,
+                },
+                Transition {
+                    from: 3,
+                    facing: Facing::Right,
+                    to: 5,
+                    edge: Edge::Top,
+                },
+                Transition {
+                    from: 5,
+                    facing: Facing::Left,
+                    to: 4,
+                    edge: Edge::Right,
+                },
+                Transition {
+                    from: 4,
+                    facing: Facing::Down,
+                    to: 1,
+                    edge: Edge::Bottom,
+                },
+                Transition {
+                    from: 1,
+                    facing: Facing::Right,
+                    to: 2,
+                    edge: Edge::Left,
+                },
+            ],
+            get_transitions(&map, &trace)
+        );
+
+        let rendered = render_faces(&map, &trace);
+        assert!(rendered.starts_with("== Face 0 at (9, 1) ==\n>>v#\n.#v.\n#.v.\n..v.\n"));
+        assert!(rendered.contains("Down -> face 3 through its Top edge: 1\n"));
+        assert!(rendered.contains(
+            "== Face 5 at (13, 9) ==\n..v.\n.#v.\n<<<.\n..#.\nLeft -> face 4 through its Right edge: 1\n"
+        ));
+    }
+}
@@ -1,9 +1,12 @@
-use itertools::iproduct;
+mod rules;
+
+use rules::{Collision, Neighbourhood, ParseRulesError, Rules};
 use std::{
-    collections::HashMap,
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
     env,
     fmt::{Display, Write},
     fs,
+    hash::{Hash, Hasher},
     ops::Add,
 };
 
@@ -12,7 +15,11 @@ fn load_input() -> String {
     fs::read_to_string(args.get(1).unwrap()).expect("Should have been able to read the file")
 }
 
-type Ordinate = i8;
+fn load_rules() -> Result<Rules, ParseRulesError> {
+    env::args().nth(2).unwrap_or_default().parse()
+}
+
+type Ordinate = i32;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Position {
@@ -31,9 +38,9 @@ impl Add for Position {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 struct Elves {
-    positions: Vec<Position>,
+    positions: HashSet<Position>,
 }
 
 impl Display for Elves {
@@ -63,76 +70,113 @@ impl Display for Elves {
 }
 
 struct ElvesIterator {
-    order: [Direction; 4],
+    rules: Rules,
+    order: Vec<Direction>,
     elves: Elves,
 }
 
 impl ElvesIterator {
-    pub fn new(elves: &Elves) -> Self {
-        let order = [
-            Direction::North,
-            Direction::South,
-            Direction::West,
-            Direction::East,
-        ];
-
+    pub fn new(elves: &Elves, rules: &Rules) -> Self {
+        let rules = rules.clone();
+        let order = rules.order.clone();
         let elves = elves.clone();
 
-        ElvesIterator { order, elves }
+        ElvesIterator {
+            rules,
+            order,
+            elves,
+        }
+    }
+
+    pub fn elves(&self) -> &Elves {
+        &self.elves
+    }
+
+    // The state the next rounds depend on: where the elves are, in a canonical order, and
+    // which way round the direction order currently is.
+    fn snapshot(&self) -> Snapshot {
+        let mut positions: Vec<Position> = self.elves.positions.iter().cloned().collect();
+        positions.sort_by_key(|pos| (pos.y, pos.x));
+
+        Snapshot {
+            order: self.order.clone(),
+            positions,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct Snapshot {
+    order: Vec<Direction>,
+    positions: Vec<Position>,
+}
+
+impl Snapshot {
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    // No elf moved in this round.
+    Settled { round: u32 },
+    // The elves are back where they were after round `start`, `length` rounds later.
+    Cycle { start: u32, length: u32 },
+    Exhausted { rounds: u32 },
+}
+
+// Moves the elves in place and yields how many of them moved each round.
 impl Iterator for ElvesIterator {
-    type Item = Elves;
+    type Item = usize;
 
     fn next(&mut self) -> Option<Self::Item> {
         log::debug!("{:?}", self.order);
 
-        let proposals: Vec<Option<Position>> = self
-            .elves
-            .positions
-            .iter()
-            .map(|pos| get_proposal(pos, &self.elves.positions, &self.order))
-            .collect();
-
-        let proposal_counts = get_proposal_counts(&proposals);
-
-        let proposals: Vec<Option<Position>> = proposals
-            .iter()
-            .cloned()
-            .map(|proposal| match proposal_counts.get(&proposal) {
-                Some(count) => {
-                    if *count > 1 {
-                        None
-                    } else {
-                        proposal
-                    }
-                }
-                None => None,
-            })
-            .collect();
-
-        let positions = self
-            .elves
-            .positions
-            .iter()
-            .zip(proposals)
-            .map(|(current_position, new_position)| match new_position {
-                Some(position) => position,
-                None => current_position.clone(),
-            })
-            .collect();
-
-        self.elves = Elves { positions };
-        self.order.rotate_left(1);
+        let positions = &self.elves.positions;
+        let mut elves: Vec<&Position> = positions.iter().collect();
+
+        if self.rules.collision == Collision::FirstWins {
+            elves.sort_by_key(|pos| (pos.y, pos.x));
+        }
+
+        // Each proposed position, the elf that proposed it and whether another elf did too.
+        let mut proposals: HashMap<Position, (Position, bool)> = HashMap::new();
+
+        for pos in elves {
+            let proposal = get_proposal(pos, positions, &self.order, self.rules.neighbourhood);
+
+            if let Some(proposal) = proposal {
+                proposals
+                    .entry(proposal)
+                    .and_modify(|(_, contested)| {
+                        *contested = self.rules.collision == Collision::AllBlocked
+                    })
+                    .or_insert((*pos, false));
+            }
+        }
+
+        let mut moved = 0;
+
+        for (proposal, (current_position, contested)) in proposals {
+            if !contested {
+                self.elves.positions.remove(&current_position);
+                self.elves.positions.insert(proposal);
+                moved += 1;
+            }
+        }
+
+        self.rules.rotation.apply(&mut self.order);
 
         log::debug!("{}", self.elves);
 
-        return Some(self.elves.clone());
+        Some(moved)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Direction {
     North,
     South,
@@ -140,16 +184,49 @@ enum Direction {
     East,
 }
 
+// The eight neighbours of a position, clockwise from north. Bit i of a neighbour mask is
+// set when there is an elf at NEIGHBOURS[i].
+const NEIGHBOURS: [(Ordinate, Ordinate); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+impl Direction {
+    fn mask(&self) -> u8 {
+        match self {
+            Direction::North => 0b1000_0011,
+            Direction::East => 0b0000_1110,
+            Direction::South => 0b0011_1000,
+            Direction::West => 0b1110_0000,
+        }
+    }
+
+    fn step(&self) -> Position {
+        match self {
+            Direction::North => Position { x: 0, y: -1 },
+            Direction::East => Position { x: 1, y: 0 },
+            Direction::South => Position { x: 0, y: 1 },
+            Direction::West => Position { x: -1, y: 0 },
+        }
+    }
+}
+
 fn parse_elves(input: &str) -> Elves {
-    let mut elves = Vec::new();
+    let mut elves = HashSet::new();
 
     for (y, row) in input.split('\n').enumerate() {
         for (x, val) in row.chars().enumerate() {
-            if val == '.' {
+            if val != '#' {
                 continue;
             }
 
-            elves.push(Position {
+            elves.insert(Position {
                 x: x as Ordinate,
                 y: y as Ordinate,
             });
@@ -159,77 +236,40 @@ fn parse_elves(input: &str) -> Elves {
     Elves { positions: elves }
 }
 
-fn has_any_elf_around(pos: &Position, elves: &[Position]) -> bool {
-    iproduct!([pos.x - 1, pos.x, pos.x + 1], [pos.y - 1, pos.y, pos.y + 1])
-        .filter(|(x, y)| !(*x == pos.x && *y == pos.y))
-        .map(|(x, y)| Position { x, y })
-        .any(|elf| elves.contains(&elf))
+fn get_neighbour_mask(pos: &Position, elves: &HashSet<Position>) -> u8 {
+    NEIGHBOURS
+        .iter()
+        .enumerate()
+        .filter(|(_, &(x, y))| elves.contains(&(*pos + Position { x, y })))
+        .fold(0, |mask, (i, _)| mask | 1 << i)
 }
 
-fn get_proposal(pos: &Position, elves: &[Position], order: &[Direction]) -> Option<Position> {
-    if !has_any_elf_around(pos, elves) {
+fn get_proposal(
+    pos: &Position,
+    elves: &HashSet<Position>,
+    order: &[Direction],
+    neighbourhood: Neighbourhood,
+) -> Option<Position> {
+    let mask = get_neighbour_mask(pos, elves) & neighbourhood.mask();
+
+    if mask == 0 {
         return None;
     }
 
-    const NORTH: (bool, Ordinate) = (true, -1);
-    const WEST: (bool, Ordinate) = (false, -1);
-    const EAST: (bool, Ordinate) = (false, 1);
-    const SOUTH: (bool, Ordinate) = (true, 1);
-
-    let order: Vec<(bool, Ordinate)> = order
+    order
         .iter()
-        .map(|direction| match direction {
-            Direction::North => NORTH,
-            Direction::South => SOUTH,
-            Direction::West => WEST,
-            Direction::East => EAST,
-        })
-        .collect();
-
-    for (is_vertical, displacement) in order {
-        let create_position = if is_vertical {
-            |a, b| Position { x: a, y: b }
-        } else {
-            |a, b| Position { x: b, y: a }
-        };
-
-        if [0, 1, -1]
-            .into_iter()
-            .map(|disp| *pos + create_position(disp, displacement))
-            .all(|pos| !elves.contains(&pos))
-        {
-            return Some(*pos + create_position(0, displacement));
-        }
-    }
-
-    None
+        .find(|direction| mask & neighbourhood.direction_mask(direction) == 0)
+        .map(|direction| *pos + direction.step())
 }
 
-fn run_process(elves: &Elves, num_rounds: i8) -> Elves {
-    let mut elves_iter = ElvesIterator::new(elves);
-
-    let mut elves = elves.clone();
+fn run_process(elves: &Elves, rules: &Rules, num_rounds: u32) -> Elves {
+    let mut elves_iter = ElvesIterator::new(elves, rules);
 
     for _ in 0..num_rounds {
-        elves = elves_iter.next().unwrap();
-    }
-
-    elves
-}
-
-fn get_proposal_counts(proposals: &[Option<Position>]) -> HashMap<&Option<Position>, u8> {
-    let mut counts = HashMap::new();
-
-    for proposal in proposals {
-        if proposal.is_some() {
-            counts
-                .entry(proposal)
-                .and_modify(|count| *count += 1)
-                .or_insert(1);
-        }
+        elves_iter.next();
     }
 
-    counts
+    elves_iter.elves().clone()
 }
 
 fn main() {
@@ -240,38 +280,62 @@ fn main() {
     let elves = parse_elves(&input);
     log::debug!("{}", &elves);
 
-    const NUM_ROUNDS: i8 = 10;
-    let elves_1 = run_process(&elves, NUM_ROUNDS);
-    log::debug!("{}", &elves);
+    let rules = load_rules().unwrap();
+
+    const NUM_ROUNDS: u32 = 10;
+    let elves_1 = run_process(&elves, &rules, NUM_ROUNDS);
+    log::debug!("{}", &elves_1);
 
     let empty_ground_tiles = count_empty_ground_tiles(&elves_1);
     println!("{}", empty_ground_tiles);
 
-    let round = first_round_no_elves_move(&elves);
-    println!("{}", round);
+    const MAX_ROUNDS: u32 = 10_000;
+    match simulate(&elves, &rules, MAX_ROUNDS) {
+        Outcome::Settled { round } => println!("{}", round),
+        Outcome::Cycle { start, length } => {
+            println!("Cycle of length {} starting after round {}", length, start)
+        }
+        Outcome::Exhausted { rounds } => println!("Still moving after {} rounds", rounds),
+    }
 }
 
-fn first_round_no_elves_move(elves: &Elves) -> u32 {
-    let mut elves_iter = ElvesIterator::new(elves);
+fn snapshot_after(elves: &Elves, rules: &Rules, num_rounds: u32) -> Snapshot {
+    let mut elves_iter = ElvesIterator::new(elves, rules);
+
+    for _ in 0..num_rounds {
+        elves_iter.next();
+    }
 
-    let mut previous_elves = elves.clone();
+    elves_iter.snapshot()
+}
 
-    let mut round = 1;
+// Only the fingerprints of earlier rounds are kept, so a repeated fingerprint is confirmed
+// by replaying up to the earlier round before calling it a cycle.
+fn simulate(elves: &Elves, rules: &Rules, max_rounds: u32) -> Outcome {
+    let mut elves_iter = ElvesIterator::new(elves, rules);
+    let mut seen: HashMap<u64, u32> = HashMap::from([(elves_iter.snapshot().fingerprint(), 0)]);
 
-    loop {
-        let new_elves = elves_iter.next().unwrap();
+    for round in 1..=max_rounds {
+        if elves_iter.next() == Some(0) {
+            return Outcome::Settled { round };
+        }
 
-        log::debug!("{}\n{}", previous_elves, new_elves);
+        let snapshot = elves_iter.snapshot();
+        let fingerprint = snapshot.fingerprint();
 
-        if previous_elves == new_elves {
-            break;
+        if let Some(&start) = seen.get(&fingerprint) {
+            if snapshot_after(elves, rules, start) == snapshot {
+                return Outcome::Cycle {
+                    start,
+                    length: round - start,
+                };
+            }
         }
 
-        previous_elves = new_elves;
-        round += 1;
+        seen.insert(fingerprint, round);
     }
 
-    round
+    Outcome::Exhausted { rounds: max_rounds }
 }
 
 fn count_empty_ground_tiles(elves: &Elves) -> u32 {
@@ -286,3 +350,112 @@ fn count_empty_ground_tiles(elves: &Elves) -> u32 {
 
     area - positions.len() as u32
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        count_empty_ground_tiles, parse_elves,
+        rules::{Collision, Neighbourhood, Rotation, Rules},
+        run_process, simulate, Direction, Outcome,
+    };
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_small_example() {
+        init();
+        let elves = parse_elves(include_str!("../test_1.txt"));
+        let expected = parse_elves("..#..\n....#\n#....\n....#\n.....\n..#..");
+
+        let actual = run_process(&elves, &Rules::default(), 10);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_count_empty_ground_tiles() {
+        init();
+        let elves = parse_elves(include_str!("../test_2.txt"));
+        let expected = 110;
+        let actual = count_empty_ground_tiles(&run_process(&elves, &Rules::default(), 10));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_simulate_settles() {
+        init();
+        let elves = parse_elves(include_str!("../test_2.txt"));
+        let expected = Outcome::Settled { round: 20 };
+        let actual = simulate(&elves, &Rules::default(), 100);
+        assert_eq!(expected, actual);
+
+        let expected = Outcome::Exhausted { rounds: 10 };
+        let actual = simulate(&elves, &Rules::default(), 10);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_collision_policies() {
+        init();
+        let elves = parse_elves("#.##\n#.##");
+        let mut rules = Rules {
+            order: vec![Direction::East, Direction::West],
+            rotation: Rotation::Fixed,
+            neighbourhood: Neighbourhood::VonNeumann,
+            collision: Collision::AllBlocked,
+        };
+
+        let expected = parse_elves("#.#.#\n#.#.#");
+        assert_eq!(expected, run_process(&elves, &rules, 1));
+
+        rules.collision = Collision::FirstWins;
+        let expected = parse_elves(".##.#\n.##.#");
+        assert_eq!(expected, run_process(&elves, &rules, 1));
+    }
+
+    #[test]
+    fn test_neighbourhoods() {
+        init();
+        let elves = parse_elves("#..\n.#.\n#..");
+        let mut rules = Rules {
+            order: vec![Direction::South, Direction::North],
+            rotation: Rotation::Right,
+            neighbourhood: Neighbourhood::Moore,
+            collision: Collision::AllBlocked,
+        };
+
+        let expected = parse_elves("#..\n...\n.#.\n...\n#..");
+        let actual = run_process(&parse_elves("...\n#..\n.#.\n#..\n..."), &rules, 1);
+        assert_eq!(expected, actual);
+
+        rules.neighbourhood = Neighbourhood::VonNeumann;
+        assert_eq!(elves, run_process(&elves, &rules, 5));
+    }
+
+    #[test]
+    fn test_simulate_cycles() {
+        init();
+        let rules: Rules = "order=NS".parse().unwrap();
+
+        let elves = parse_elves("##");
+        let expected = Outcome::Cycle {
+            start: 0,
+            length: 2,
+        };
+        assert_eq!(expected, simulate(&elves, &rules, 100));
+
+        let elves = parse_elves("##\n#.");
+        let expected = Outcome::Cycle {
+            start: 1,
+            length: 2,
+        };
+        assert_eq!(expected, simulate(&elves, &rules, 100));
+
+        // The elves are back where they started after two rounds, but facing a different
+        // direction order, which then pulls them apart.
+        let elves = parse_elves("##");
+        let expected = Outcome::Settled { round: 4 };
+        assert_eq!(expected, simulate(&elves, &Rules::default(), 100));
+    }
+}
@@ -0,0 +1,153 @@
+use std::str::FromStr;
+
+use crate::Direction;
+
+// What happens to the direction order after each round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Fixed,
+    Left,
+    Right,
+}
+
+// Which neighbours count: all eight (Moore) or only the four orthogonal ones (von
+// Neumann). An elf only moves if one of its neighbours is occupied, and only towards a
+// direction whose neighbours in that side are all empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    Moore,
+    VonNeumann,
+}
+
+// What happens when several elves propose the same position: either none of them move,
+// or the first in reading order does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collision {
+    AllBlocked,
+    FirstWins,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub order: Vec<Direction>,
+    pub rotation: Rotation,
+    pub neighbourhood: Neighbourhood,
+    pub collision: Collision,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            order: vec![
+                Direction::North,
+                Direction::South,
+                Direction::West,
+                Direction::East,
+            ],
+            rotation: Rotation::Left,
+            neighbourhood: Neighbourhood::Moore,
+            collision: Collision::AllBlocked,
+        }
+    }
+}
+
+impl Rotation {
+    pub fn apply(&self, order: &mut [Direction]) {
+        match self {
+            Rotation::Fixed => {}
+            Rotation::Left => order.rotate_left(1),
+            Rotation::Right => order.rotate_right(1),
+        }
+    }
+}
+
+impl Neighbourhood {
+    // Bits of the neighbour mask that are part of this neighbourhood.
+    pub fn mask(&self) -> u8 {
+        match self {
+            Neighbourhood::Moore => 0b1111_1111,
+            Neighbourhood::VonNeumann => 0b0101_0101,
+        }
+    }
+
+    pub fn direction_mask(&self, direction: &Direction) -> u8 {
+        direction.mask() & self.mask()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseRulesError(String);
+
+fn parse_direction(c: char) -> Result<Direction, ParseRulesError> {
+    match c {
+        'N' => Ok(Direction::North),
+        'S' => Ok(Direction::South),
+        'W' => Ok(Direction::West),
+        'E' => Ok(Direction::East),
+        _ => Err(ParseRulesError(format!("Unknown direction {}", c))),
+    }
+}
+
+// Space separated `key=value` settings, e.g. "order=NSWE rotation=left
+// neighbourhood=moore collision=all-blocked". Missing settings keep their defaults.
+impl FromStr for Rules {
+    type Err = ParseRulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = Rules::default();
+
+        for setting in s.split_whitespace() {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| ParseRulesError(format!("Expected key=value, got {}", setting)))?;
+
+            match (key, value) {
+                ("order", order) => {
+                    rules.order = order
+                        .chars()
+                        .map(parse_direction)
+                        .collect::<Result<_, _>>()?
+                }
+                ("rotation", "fixed") => rules.rotation = Rotation::Fixed,
+                ("rotation", "left") => rules.rotation = Rotation::Left,
+                ("rotation", "right") => rules.rotation = Rotation::Right,
+                ("neighbourhood", "moore") => rules.neighbourhood = Neighbourhood::Moore,
+                ("neighbourhood", "von-neumann") => rules.neighbourhood = Neighbourhood::VonNeumann,
+                ("collision", "all-blocked") => rules.collision = Collision::AllBlocked,
+                ("collision", "first-wins") => rules.collision = Collision::FirstWins,
+                _ => return Err(ParseRulesError(format!("Unknown setting {}", setting))),
+            }
+        }
+
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Direction;
+
+    use super::{Collision, Neighbourhood, ParseRulesError, Rotation, Rules};
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(Ok(Rules::default()), "".parse());
+        assert_eq!(
+            Ok(Rules {
+                order: vec![Direction::East, Direction::North],
+                rotation: Rotation::Fixed,
+                neighbourhood: Neighbourhood::VonNeumann,
+                collision: Collision::FirstWins,
+            }),
+            "order=EN rotation=fixed neighbourhood=von-neumann collision=first-wins".parse()
+        );
+        assert_eq!(
+            Err(ParseRulesError("Unknown direction X".to_string())),
+            "order=NX".parse::<Rules>()
+        );
+        assert_eq!(
+            Err(ParseRulesError("Unknown setting rotation=up".to_string())),
+            "rotation=up".parse::<Rules>()
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{Coordinate, Valley, ValleyIterator};
+
+fn gcd(a: usize, b: usize) -> usize {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+// Every blizzard is back where it started after lcm(inner width, inner height) minutes,
+// so which tiles are clear only needs working out once for each minute of that period.
+pub struct Forecast {
+    period: usize,
+    width: usize,
+    height: usize,
+    walls: Vec<bool>,
+    clear: Vec<Vec<bool>>,
+}
+
+impl Forecast {
+    pub fn new(valley: &Valley) -> Forecast {
+        let (width, height) = (valley.get_width(), valley.get_height());
+        let (inner_width, inner_height) = (width - 2, height - 2);
+        let period = inner_width / gcd(inner_width, inner_height) * inner_height;
+
+        let walls: Vec<bool> = (0..width * height)
+            .map(|i| valley.is_wall(valley.get_coordinate(i)))
+            .collect();
+
+        let get_clear = |valley: &Valley| {
+            valley
+                .get_occupancy()
+                .into_iter()
+                .zip(walls.iter())
+                .map(|(blizzards, &wall)| blizzards == 0 && !wall)
+                .collect()
+        };
+
+        let mut clear = vec![get_clear(valley)];
+        clear.extend(
+            ValleyIterator {
+                valley: valley.clone(),
+            }
+            .take(period - 1)
+            .map(|valley| get_clear(&valley)),
+        );
+
+        Forecast {
+            period,
+            width,
+            height,
+            walls,
+            clear,
+        }
+    }
+
+    pub fn get_period(&self) -> usize {
+        self.period
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_wall(&self, position: Coordinate) -> bool {
+        self.walls[position.y * self.width + position.x]
+    }
+
+    pub fn is_clear(&self, position: Coordinate, time: u32) -> bool {
+        self.clear[time as usize % self.period][position.y * self.width + position.x]
+    }
+}
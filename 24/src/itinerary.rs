@@ -0,0 +1,241 @@
+use std::cmp::Reverse;
+
+use crate::{
+    forecast::Forecast,
+    get_next_positions,
+    route::{Move, Route},
+    Coordinate,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ItineraryError {
+    TooFewWaypoints,
+    OutOfBounds {
+        waypoint: usize,
+        position: Coordinate,
+    },
+    Wall {
+        waypoint: usize,
+        position: Coordinate,
+    },
+    Unreachable {
+        leg: usize,
+        from: Coordinate,
+        to: Coordinate,
+    },
+}
+
+fn check_waypoints(forecast: &Forecast, waypoints: &[Coordinate]) -> Result<(), ItineraryError> {
+    if waypoints.len() < 2 {
+        return Err(ItineraryError::TooFewWaypoints);
+    }
+
+    for (waypoint, &position) in waypoints.iter().enumerate() {
+        if position.x >= forecast.get_width() || position.y >= forecast.get_height() {
+            return Err(ItineraryError::OutOfBounds { waypoint, position });
+        }
+
+        if forecast.is_wall(position) {
+            return Err(ItineraryError::Wall { waypoint, position });
+        }
+    }
+
+    Ok(())
+}
+
+// Visits the waypoints in order in the least total time, returning the route of each leg.
+// Arriving at a waypoint as early as possible is not always best, since the expedition may
+// not be able to wait there, so the search runs over (position, minute, leg) together.
+// Blizzards repeat every period, so only the first visit to a state in each phase of the
+// period is explored.
+pub fn find_itinerary(
+    forecast: &Forecast,
+    waypoints: &[Coordinate],
+    start_time: u32,
+) -> Result<Vec<Route>, ItineraryError> {
+    check_waypoints(forecast, waypoints)?;
+
+    let legs = waypoints.len() - 1;
+    let (width, height) = (forecast.get_width(), forecast.get_height());
+    let states = forecast.get_period() * width * height;
+    let index = |position: Coordinate, time: u32, leg: usize| {
+        leg * states
+            + ((time as usize % forecast.get_period()) * height + position.y) * width
+            + position.x
+    };
+
+    // Reaching the next waypoint starts the following leg straight away.
+    let advance = |position: Coordinate, mut leg: usize| {
+        while leg < legs && position == waypoints[leg + 1] {
+            leg += 1;
+        }
+
+        leg
+    };
+
+    // Where each explored state was reached from, which doubles as the visited set.
+    let mut previous: Vec<Option<(Coordinate, usize)>> = vec![None; (legs + 1) * states];
+
+    let start = waypoints[0];
+    let start_leg = advance(start, 0);
+    previous[index(start, start_time, start_leg)] = Some((start, start_leg));
+
+    let mut frontier = vec![(start, start_leg)];
+    let mut furthest_leg = start_leg;
+    let mut current_time = start_time;
+
+    while !frontier.is_empty() {
+        if let Some(&(end, _)) = frontier.iter().find(|(_, leg)| *leg == legs) {
+            let mut moves = vec![Vec::new(); legs];
+            let (mut position, mut time, mut leg) = (end, current_time, legs);
+
+            while time > start_time {
+                let (from, from_leg) = previous[index(position, time, leg)].unwrap();
+                moves[from_leg].push(Move::between(from, position));
+                (position, time, leg) = (from, time - 1, from_leg);
+            }
+
+            let mut time = start_time;
+
+            return Ok(waypoints
+                .iter()
+                .zip(moves)
+                .map(|(&start, mut moves)| {
+                    moves.reverse();
+
+                    let route = Route {
+                        start,
+                        start_time: time,
+                        moves,
+                    };
+                    time = route.end_time();
+
+                    route
+                })
+                .collect());
+        }
+
+        // Expanding the furthest legs first means that, of the equally quick itineraries,
+        // the one that reaches each waypoint earliest is returned.
+        frontier.sort_by_key(|&(_, leg)| Reverse(leg));
+
+        let mut next_frontier = Vec::new();
+
+        for (current_position, current_leg) in frontier {
+            for next_position in get_next_positions(current_position, forecast, current_time + 1) {
+                let next_leg = advance(next_position, current_leg);
+                let next_index = index(next_position, current_time + 1, next_leg);
+
+                if previous[next_index].is_none() {
+                    previous[next_index] = Some((current_position, current_leg));
+                    next_frontier.push((next_position, next_leg));
+                    furthest_leg = furthest_leg.max(next_leg);
+                }
+            }
+        }
+
+        frontier = next_frontier;
+        current_time += 1;
+    }
+
+    Err(ItineraryError::Unreachable {
+        leg: furthest_leg,
+        from: waypoints[furthest_leg],
+        to: waypoints[furthest_leg + 1],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{forecast::Forecast, Coordinate, Valley};
+
+    use super::{find_itinerary, ItineraryError};
+
+    #[test]
+    fn test_there_and_back_again() {
+        let valley = Valley::from_str(include_str!("../test_2.txt")).unwrap();
+        let forecast = Forecast::new(&valley);
+        let entrances = valley.get_entrances();
+        let (start, end) = (entrances[0], entrances[1]);
+
+        let legs = find_itinerary(&forecast, &[start, end, start, end], 0).unwrap();
+        let times: Vec<(u32, usize)> = legs
+            .iter()
+            .map(|leg| (leg.start_time, leg.moves.len()))
+            .collect();
+        assert_eq!(vec![(0, 18), (18, 23), (41, 13)], times);
+
+        for (leg, waypoint) in legs.iter().zip([end, start, end]) {
+            assert_eq!(Some(&waypoint), leg.positions().last());
+        }
+    }
+
+    #[test]
+    fn test_waypoint_errors() {
+        let valley = Valley::from_str(include_str!("../test_2.txt")).unwrap();
+        let forecast = Forecast::new(&valley);
+        let start = Coordinate { x: 1, y: 0 };
+
+        assert_eq!(
+            Err(ItineraryError::TooFewWaypoints),
+            find_itinerary(&forecast, &[start], 0)
+        );
+        assert_eq!(
+            Err(ItineraryError::Wall {
+                waypoint: 1,
+                position: Coordinate { x: 0, y: 2 }
+            }),
+            find_itinerary(&forecast, &[start, Coordinate { x: 0, y: 2 }], 0)
+        );
+        assert_eq!(
+            Err(ItineraryError::OutOfBounds {
+                waypoint: 2,
+                position: Coordinate { x: 8, y: 2 }
+            }),
+            find_itinerary(&forecast, &[start, start, Coordinate { x: 8, y: 2 }], 0)
+        );
+
+        let valley = Valley::from_str("#.###\n#.#.#\n###.#").unwrap();
+        let forecast = Forecast::new(&valley);
+        let (open, closed) = (Coordinate { x: 1, y: 1 }, Coordinate { x: 3, y: 1 });
+
+        assert_eq!(
+            Err(ItineraryError::Unreachable {
+                leg: 1,
+                from: open,
+                to: closed
+            }),
+            find_itinerary(&forecast, &[start, open, closed], 0)
+        );
+    }
+
+    #[test]
+    fn test_interior_waypoint() {
+        let valley = Valley::from_str(include_str!("../test_2.txt")).unwrap();
+        let forecast = Forecast::new(&valley);
+        let waypoint = Coordinate { x: 3, y: 3 };
+
+        let legs = find_itinerary(
+            &forecast,
+            &[
+                Coordinate { x: 1, y: 0 },
+                waypoint,
+                Coordinate { x: 6, y: 5 },
+            ],
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(2, legs.len());
+        assert_eq!(legs[0].end_time(), legs[1].start_time);
+        assert_eq!(Some(&waypoint), legs[0].positions().last());
+
+        for leg in legs.iter() {
+            for (minute, position) in leg.positions().into_iter().enumerate() {
+                assert!(forecast.is_clear(position, leg.start_time + minute as u32));
+            }
+        }
+    }
+}
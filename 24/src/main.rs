@@ -1,6 +1,12 @@
+mod forecast;
+mod itinerary;
+mod route;
+
+use forecast::Forecast;
+use itinerary::{find_itinerary, ItineraryError};
+use route::{render_route, Route};
 use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashMap},
+    collections::HashMap,
     env,
     fmt::{Display, Write},
     fs, process,
@@ -13,37 +19,79 @@ enum Direction {
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+const DIRECTIONS: [(Direction, char); 8] = [
+    (Direction::Up, '^'),
+    (Direction::Down, 'v'),
+    (Direction::Left, '<'),
+    (Direction::Right, '>'),
+    (Direction::UpLeft, '↖'),
+    (Direction::UpRight, '↗'),
+    (Direction::DownLeft, '↙'),
+    (Direction::DownRight, '↘'),
+];
+
+impl Direction {
+    fn from_glyph(c: char) -> Option<Direction> {
+        DIRECTIONS
+            .iter()
+            .find(|(_, glyph)| *glyph == c)
+            .map(|(direction, _)| *direction)
+    }
+
+    fn glyph(&self) -> char {
+        DIRECTIONS
+            .iter()
+            .find(|(direction, _)| direction == self)
+            .unwrap()
+            .1
+    }
+
+    fn velocity(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (1, -1),
+            Direction::DownLeft => (-1, 1),
+            Direction::DownRight => (1, 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Blizzard {
+    id: u16,
+    position: Coordinate,
+    velocity: Direction,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ValleyPart {
     Wall,
     ClearGround,
-    Blizzard(u16, Direction),
-    Overlap(
-        Option<(u16, Direction)>,
-        Option<(u16, Direction)>,
-        Option<(u16, Direction)>,
-        Option<(u16, Direction)>,
-    ),
+    Blizzard(Direction),
+    Overlap(usize),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Valley {
-    data: Vec<ValleyPart>,
+    walls: Vec<bool>,
+    blizzards: Vec<Blizzard>,
     width: usize,
     height: usize,
 }
 
 impl Valley {
-    fn get_part(&self, coordinate: Coordinate) -> ValleyPart {
-        let index = coordinate.y * self.width + coordinate.x;
-        self.data[index]
-    }
-
-    fn set_part(&mut self, coordinate: Coordinate, part: ValleyPart) {
-        let index = coordinate.y * self.width + coordinate.x;
-        self.data[index] = part;
+    fn get_index(&self, coordinate: Coordinate) -> usize {
+        coordinate.y * self.width + coordinate.x
     }
 
     fn get_coordinate(&self, index: usize) -> Coordinate {
@@ -54,39 +102,50 @@ impl Valley {
         }
     }
 
-    fn get_blizzards(&self) -> Vec<(ValleyPart, Coordinate)> {
-        let blizzards = self
-            .data
-            .iter()
-            .enumerate()
-            .filter(|(_, part)| match part {
-                ValleyPart::Blizzard(_, _) => true,
-                _ => false,
-            })
-            .map(|(i, part)| (*part, self.get_coordinate(i)))
-            .collect();
+    fn is_wall(&self, coordinate: Coordinate) -> bool {
+        self.walls[self.get_index(coordinate)]
+    }
+
+    // How many blizzards are in each tile.
+    fn get_occupancy(&self) -> Vec<usize> {
+        let mut occupancy = vec![0; self.walls.len()];
+
+        for blizzard in self.blizzards.iter() {
+            occupancy[self.get_index(blizzard.position)] += 1;
+        }
+
+        occupancy
+    }
 
-        let overlaps: Vec<(ValleyPart, Coordinate)> = self
-            .data
+    fn get_parts(&self) -> Vec<ValleyPart> {
+        let mut parts: Vec<ValleyPart> = self
+            .walls
             .iter()
-            .enumerate()
-            .map(|(i, part)| match part {
-                ValleyPart::Overlap(a, b, c, d) => [a, b, c, d]
-                    .iter()
-                    .filter(|part| part.is_some())
-                    .map(|part| {
-                        (
-                            ValleyPart::Blizzard(part.unwrap().0, part.unwrap().1),
-                            self.get_coordinate(i),
-                        )
-                    })
-                    .collect(),
-                _ => vec![],
+            .map(|&wall| match wall {
+                true => ValleyPart::Wall,
+                false => ValleyPart::ClearGround,
             })
-            .flatten()
             .collect();
 
-        [blizzards, overlaps].concat()
+        for blizzard in self.blizzards.iter() {
+            let index = self.get_index(blizzard.position);
+
+            parts[index] = match parts[index] {
+                ValleyPart::Blizzard(_) => ValleyPart::Overlap(2),
+                ValleyPart::Overlap(count) => ValleyPart::Overlap(count + 1),
+                _ => ValleyPart::Blizzard(blizzard.velocity),
+            };
+        }
+
+        parts
+    }
+
+    fn get_blizzards(&self) -> &[Blizzard] {
+        &self.blizzards
+    }
+
+    fn set_blizzards(&mut self, blizzards: Vec<Blizzard>) {
+        self.blizzards = blizzards;
     }
 
     fn get_height(&self) -> usize {
@@ -97,81 +156,34 @@ impl Valley {
         self.width
     }
 
-    fn remove_part(&mut self, position: Coordinate, part: ValleyPart) {
-        let part_at_position = self.get_part(position);
-
-        if part_at_position == part {
-            self.set_part(position, ValleyPart::ClearGround);
-        }
-
-        if let ValleyPart::Overlap(a, b, c, d) = part_at_position {
-            if let ValleyPart::Blizzard(id, dir) = part {
-                let x = Some((id, dir));
-                let mut new_part = if x == a {
-                    ValleyPart::Overlap(None, b, c, d)
-                } else if x == b {
-                    ValleyPart::Overlap(a, None, c, d)
-                } else if x == c {
-                    ValleyPart::Overlap(a, b, None, d)
-                } else if x == d {
-                    ValleyPart::Overlap(a, b, c, None)
-                } else {
-                    ValleyPart::Overlap(a, b, c, d)
-                };
-
-                if let ValleyPart::Overlap(a, b, c, d) = new_part {
-                    if a.is_none() && b.is_none() && c.is_none() && d.is_none() {
-                        new_part = ValleyPart::ClearGround;
-                    }
-                }
-
-                self.set_part(position, new_part);
-            }
-        }
+    fn is_border(&self, position: Coordinate) -> bool {
+        position.x == 0
+            || position.y == 0
+            || position.x == self.width - 1
+            || position.y == self.height - 1
     }
 
-    fn set_blizzards(&mut self, blizzards: Vec<(ValleyPart, Coordinate, Coordinate)>) {
-        let mut map = HashMap::new();
+    // Gaps in the outer wall, in reading order.
+    fn get_entrances(&self) -> Vec<Coordinate> {
+        (0..self.walls.len())
+            .map(|i| self.get_coordinate(i))
+            .filter(|position| self.is_border(*position) && !self.is_wall(*position))
+            .collect()
+    }
 
-        for (part, old_position, new_position) in blizzards {
-            map.entry(new_position)
-                .and_modify(|parts: &mut Vec<(ValleyPart, Coordinate)>| {
-                    parts.push((part, old_position))
-                })
-                .or_insert(vec![(part, old_position)]);
-        }
+    // Where a blizzard ends up after a minute, wrapping around inside the outer wall.
+    fn blow(&self, blizzard: &Blizzard) -> Blizzard {
+        let (dx, dy) = blizzard.velocity.velocity();
+        let wrap = |ordinate: usize, delta: isize, length: usize| {
+            (ordinate as isize - 1 + delta).rem_euclid(length as isize - 2) as usize + 1
+        };
 
-        for (new_position, parts) in map {
-            if parts.len() == 1 {
-                let (part, old_position) = parts[0];
-                self.set_part(new_position, part);
-                self.remove_part(old_position, part);
-            } else {
-                let part = ValleyPart::Overlap(
-                    parts.get(0).map(|(part, _)| match part {
-                        ValleyPart::Blizzard(id, dir) => (*id, *dir),
-                        _ => panic!(),
-                    }),
-                    parts.get(1).map(|(part, _)| match part {
-                        ValleyPart::Blizzard(id, dir) => (*id, *dir),
-                        _ => panic!(),
-                    }),
-                    parts.get(2).map(|(part, _)| match part {
-                        ValleyPart::Blizzard(id, dir) => (*id, *dir),
-                        _ => panic!(),
-                    }),
-                    parts.get(3).map(|(part, _)| match part {
-                        ValleyPart::Blizzard(id, dir) => (*id, *dir),
-                        _ => panic!(),
-                    }),
-                );
-
-                self.set_part(new_position, part);
-
-                for (part, old_position) in parts {
-                    self.remove_part(old_position, part)
-                }
-            }
+        Blizzard {
+            position: Coordinate {
+                x: wrap(blizzard.position.x, dx, self.width),
+                y: wrap(blizzard.position.y, dy, self.height),
+            },
+            ..*blizzard
         }
     }
 }
@@ -179,74 +191,123 @@ impl Valley {
 #[derive(Debug, PartialEq, Eq)]
 struct ParseValleyError;
 
+// Reads the legend the alternate form of `Display` writes under the valley: a line per
+// overlap, giving its position and the direction of every blizzard in it.
+fn parse_overlaps(s: &str) -> Result<HashMap<Coordinate, Vec<Direction>>, ParseValleyError> {
+    s.split('\n')
+        .filter(|val| val != &"")
+        .map(|line| {
+            let (position, glyphs) = line.split_once(' ').ok_or(ParseValleyError)?;
+            let (x, y) = position.split_once(',').ok_or(ParseValleyError)?;
+            let position = Coordinate {
+                x: x.parse().map_err(|_| ParseValleyError)?,
+                y: y.parse().map_err(|_| ParseValleyError)?,
+            };
+            let directions = glyphs
+                .chars()
+                .map(|c| Direction::from_glyph(c).ok_or(ParseValleyError))
+                .collect::<Result<Vec<Direction>, ParseValleyError>>()?;
+
+            Ok((position, directions))
+        })
+        .collect()
+}
+
+// Besides the puzzle's `#.^v<>`, reads the diagonal blizzards `↖↗↙↘` and the counts that
+// `Display` writes where blizzards overlap. A count only says how many blizzards there are, so
+// every count needs a line in the legend of the alternate form saying which ways they blow,
+// which lets a valley at any minute be written out and read back in to carry on from there.
+// Only the alternate form round-trips: the plain form of a valley with overlaps has counts but
+// no legend, and is rejected rather than guessing directions for them.
 impl FromStr for Valley {
     type Err = ParseValleyError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut blizzard_count = 0..;
+        let s = s.trim_start_matches('\n');
+        let (grid, legend) = s.split_once("\n\n").unwrap_or((s, ""));
+        let mut overlaps = parse_overlaps(legend)?;
 
-        let parsed_rows: Result<Vec<Vec<ValleyPart>>, ParseValleyError> = s
+        let rows: Vec<Vec<char>> = grid
             .split('\n')
             .filter(|val| val != &"")
-            .map(|row| {
-                row.chars()
-                    .map(|char| match char {
-                        '#' => Ok(ValleyPart::Wall),
-                        '.' => Ok(ValleyPart::ClearGround),
-                        '^' => Ok(ValleyPart::Blizzard(
-                            blizzard_count.next().unwrap(),
-                            Direction::Up,
-                        )),
-                        'v' => Ok(ValleyPart::Blizzard(
-                            blizzard_count.next().unwrap(),
-                            Direction::Down,
-                        )),
-                        '<' => Ok(ValleyPart::Blizzard(
-                            blizzard_count.next().unwrap(),
-                            Direction::Left,
-                        )),
-                        '>' => Ok(ValleyPart::Blizzard(
-                            blizzard_count.next().unwrap(),
-                            Direction::Right,
-                        )),
-                        _ => Err(ParseValleyError),
-                    })
-                    .collect()
-            })
+            .map(|row| row.chars().collect())
             .collect();
 
-        let parsed_rows: Vec<Vec<ValleyPart>> = parsed_rows?;
+        let width = rows.first().ok_or(ParseValleyError)?.len();
+        let height = rows.len();
 
-        let width = *&parsed_rows[0].len();
-        let height = *&parsed_rows.len();
-        let data = parsed_rows.into_iter().flatten().collect();
+        if width < 3 || height < 3 || rows.iter().any(|row| row.len() != width) {
+            return Err(ParseValleyError);
+        }
 
-        Ok(Valley {
-            data,
+        let mut valley = Valley {
+            walls: Vec::new(),
+            blizzards: Vec::new(),
             width,
             height,
-        })
+        };
+        let mut blizzard_count = 0..;
+
+        for (i, c) in rows.into_iter().flatten().enumerate() {
+            let position = valley.get_coordinate(i);
+            valley.walls.push(c == '#');
+
+            let directions = match (c, Direction::from_glyph(c)) {
+                ('#' | '.', _) => continue,
+                (_, Some(direction)) => vec![direction],
+                ('2'..='9' | '*', _) => {
+                    let directions = overlaps.remove(&position).ok_or(ParseValleyError)?;
+                    let count_matches = match c.to_digit(10) {
+                        Some(count) => directions.len() == count as usize,
+                        None => directions.len() > 9,
+                    };
+
+                    if !count_matches {
+                        return Err(ParseValleyError);
+                    }
+
+                    directions
+                }
+                _ => return Err(ParseValleyError),
+            };
+
+            if valley.is_border(position) {
+                return Err(ParseValleyError);
+            }
+
+            for velocity in directions {
+                valley.blizzards.push(Blizzard {
+                    id: blizzard_count.next().unwrap(),
+                    position,
+                    velocity,
+                });
+            }
+        }
+
+        // Every line of the legend has to describe a count in the valley.
+        if !overlaps.is_empty() {
+            return Err(ParseValleyError);
+        }
+
+        Ok(valley)
     }
 }
 
+// Draws the valley as the puzzle does. The alternate form, `{:#}`, adds a legend giving the
+// directions of the blizzards in every overlap, so that the valley can be parsed back. The plain
+// form only parses back when no blizzards overlap.
 impl Display for Valley {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_char('\n')?;
 
-        for (i, part) in self.data.iter().enumerate() {
+        let parts = self.get_parts();
+
+        for (i, part) in parts.iter().enumerate() {
             let c = match part {
                 ValleyPart::Wall => '#',
                 ValleyPart::ClearGround => '.',
-                ValleyPart::Blizzard(_, direction) => match direction {
-                    Direction::Up => '^',
-                    Direction::Down => 'v',
-                    Direction::Left => '<',
-                    Direction::Right => '>',
-                },
-                ValleyPart::Overlap(a, b, c, d) => {
-                    let count = [a, b, c, d].into_iter().filter(|v| v.is_some()).count();
-                    char::from_digit(count as u32, 10).unwrap()
-                }
+                ValleyPart::Blizzard(direction) => direction.glyph(),
+                ValleyPart::Overlap(count) => char::from_digit(*count as u32, 10).unwrap_or('*'),
             };
 
             f.write_char(c)?;
@@ -256,6 +317,37 @@ impl Display for Valley {
             }
         }
 
+        if !f.alternate() {
+            return Ok(());
+        }
+
+        let overlaps: Vec<usize> = (0..parts.len())
+            .filter(|&i| matches!(parts[i], ValleyPart::Overlap(_)))
+            .collect();
+
+        if !overlaps.is_empty() {
+            f.write_char('\n')?;
+        }
+
+        for i in overlaps {
+            let position = self.get_coordinate(i);
+            let mut directions: Vec<Direction> = self
+                .blizzards
+                .iter()
+                .filter(|blizzard| blizzard.position == position)
+                .map(|blizzard| blizzard.velocity)
+                .collect();
+            directions.sort_by_key(|direction| {
+                DIRECTIONS.iter().position(|(other, _)| other == direction)
+            });
+
+            write!(f, "{},{} ", position.x, position.y)?;
+            for direction in directions {
+                f.write_char(direction.glyph())?;
+            }
+            f.write_char('\n')?;
+        }
+
         Ok(())
     }
 }
@@ -275,103 +367,61 @@ impl Iterator for ValleyIterator {
     type Item = Valley;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let blizzards = self.valley.get_blizzards();
-
-        let first_open_y = 1;
-        let last_open_y = self.valley.get_height() - 2;
-
-        let first_open_x = 1;
-        let last_open_x = self.valley.get_width() - 2;
-
-        let new_positions: Vec<(ValleyPart, Coordinate, Coordinate)> = blizzards
+        let blizzards = self
+            .valley
+            .get_blizzards()
             .iter()
-            .map(|(blizzard, position)| {
-                (
-                    *blizzard,
-                    *position,
-                    match blizzard {
-                        ValleyPart::Blizzard(_, Direction::Up) => {
-                            if position.y == first_open_y {
-                                Coordinate {
-                                    x: position.x,
-                                    y: last_open_y,
-                                }
-                            } else {
-                                Coordinate {
-                                    x: position.x,
-                                    y: position.y - 1,
-                                }
-                            }
-                        }
-                        ValleyPart::Blizzard(_, Direction::Down) => {
-                            if position.y == last_open_y {
-                                Coordinate {
-                                    x: position.x,
-                                    y: first_open_y,
-                                }
-                            } else {
-                                Coordinate {
-                                    x: position.x,
-                                    y: position.y + 1,
-                                }
-                            }
-                        }
-                        ValleyPart::Blizzard(_, Direction::Left) => {
-                            if position.x == first_open_x {
-                                Coordinate {
-                                    x: last_open_x,
-                                    y: position.y,
-                                }
-                            } else {
-                                Coordinate {
-                                    x: position.x - 1,
-                                    y: position.y,
-                                }
-                            }
-                        }
-                        ValleyPart::Blizzard(_, Direction::Right) => {
-                            if position.x == last_open_x {
-                                Coordinate {
-                                    x: first_open_x,
-                                    y: position.y,
-                                }
-                            } else {
-                                Coordinate {
-                                    x: position.x + 1,
-                                    y: position.y,
-                                }
-                            }
-                        }
-                        _ => todo!(),
-                    },
-                )
-            })
+            .map(|blizzard| self.valley.blow(blizzard))
             .collect();
 
-        self.valley.set_blizzards(new_positions);
+        self.valley.set_blizzards(blizzards);
 
         Some(self.valley.clone())
     }
 }
 
-#[derive(Eq, PartialEq, Debug)]
-struct State {
-    position: Coordinate,
-    predicted_time: u32,
-    current_time: u32,
-    valley: Valley,
+fn load_output_format() -> Option<String> {
+    env::args().nth(2)
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.predicted_time.cmp(&other.predicted_time))
-    }
+fn parse_waypoints(s: &str) -> Vec<Coordinate> {
+    s.split_whitespace()
+        .map(|waypoint| {
+            let (x, y) = waypoint
+                .split_once(',')
+                .unwrap_or_else(|| panic!("Expected x,y, got {}", waypoint));
+
+            Coordinate {
+                x: x.parse().unwrap(),
+                y: y.parse().unwrap(),
+            }
+        })
+        .collect()
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
-    }
+fn plan(forecast: &Forecast, waypoints: &[Coordinate]) -> Vec<Route> {
+    find_itinerary(forecast, waypoints, 0).unwrap_or_else(|error| {
+        match error {
+            ItineraryError::TooFewWaypoints => eprintln!("Need at least two waypoints!"),
+            ItineraryError::OutOfBounds { waypoint, position } => eprintln!(
+                "Waypoint {} at {},{} is outside the valley!",
+                waypoint, position.x, position.y
+            ),
+            ItineraryError::Wall { waypoint, position } => eprintln!(
+                "Waypoint {} at {},{} is a wall!",
+                waypoint, position.x, position.y
+            ),
+            ItineraryError::Unreachable { leg, from, to } => eprintln!(
+                "Leg {} from {},{} to {},{} can't be finished!",
+                leg + 1,
+                from.x,
+                from.y,
+                to.x,
+                to.y
+            ),
+        }
+        process::exit(1);
+    })
 }
 
 fn main() {
@@ -382,87 +432,66 @@ fn main() {
         eprintln!("Problem parsing valley!");
         process::exit(1);
     });
+    let forecast = Forecast::new(&valley);
 
-    let start = Coordinate { x: 1, y: 0 };
-    let end = Coordinate {
-        x: &valley.get_width() - 2,
-        y: &valley.get_height() - 1,
-    };
-    let (time, valley) = find_quickest_time_to_goal(start, end, valley).unwrap();
-    println!("{}", time);
-
-    let (time_back_to_start, valley) = find_quickest_time_to_goal(end, start, valley).unwrap();
-    log::info!(
-        "trip back to the start takes {} minutes",
-        time_back_to_start
-    );
+    let entrances = valley.get_entrances();
+    let (start, end) = (entrances[0], entrances[entrances.len() - 1]);
 
-    let (time_back_to_end, _) = find_quickest_time_to_goal(start, end, valley).unwrap();
-    log::info!("trip back to the goal takes {} minutes", time_back_to_end);
+    let there = plan(&forecast, &[start, end]);
+    println!("{}", there[0].end_time());
 
-    let total_time = time + time_back_to_start + time_back_to_end;
-    println!("{}", total_time);
-}
-
-fn find_quickest_time_to_goal(
-    start: Coordinate,
-    end: Coordinate,
-    valley: Valley,
-) -> Option<(u32, Valley)> {
-    log::debug!("{}", valley);
-
-    let mut queue = BinaryHeap::new();
-
-    fn h(pos: Coordinate, end: Coordinate) -> u32 {
-        let end_x = end.x as i32;
-        let pos_x = pos.x as i32;
-        let end_y = end.y as i32;
-        let pos_y = pos.y as i32;
+    let legs = match load_output_format().as_deref() {
+        Some("--waypoints") => {
+            let waypoints = parse_waypoints(&env::args().nth(3).expect("Expected waypoints"));
+            plan(&forecast, &waypoints)
+        }
+        _ => plan(&forecast, &[start, end, start, end]),
+    };
 
-        ((end_x - pos_x).abs() + (end_y - pos_y).abs())
-            .try_into()
-            .unwrap()
+    for (i, leg) in legs.iter().enumerate() {
+        log::info!(
+            "leg {} departs at minute {} and takes {} minutes",
+            i + 1,
+            leg.start_time,
+            leg.moves.len()
+        );
     }
 
-    queue.push(Reverse(State {
-        position: start,
-        predicted_time: 0 + h(start, end),
-        current_time: 0,
-        valley,
-    }));
-
-    while let Some(Reverse(state)) = queue.pop() {
-        let State {
-            position: current_position,
-            current_time,
-            predicted_time: _,
-            valley,
-        } = state;
-
-        if current_position == end {
-            return Some((current_time, valley));
-        }
+    let mut route = legs[0].clone();
+    for leg in legs.into_iter().skip(1) {
+        route.append(leg);
+    }
 
-        let next_valley = ValleyIterator { valley }.next().unwrap();
+    println!("{}", route.end_time());
 
-        for next_position in get_next_positions(current_position, &next_valley) {
-            let new_state = Reverse(State {
-                position: next_position,
-                predicted_time: current_time + 1 + h(next_position, end),
-                current_time: current_time + 1,
-                valley: next_valley.clone(),
-            });
+    if let Some(format) = load_output_format() {
+        match format.as_str() {
+            "--waypoints" => {}
+            "--moves" => {
+                for step in route.moves.iter() {
+                    println!("{}", step);
+                }
+            }
+            "--render" => {
+                let rendered = render_route(&valley, &route);
 
-            if !queue.iter().any(|state| state == &new_state) {
-                queue.push(new_state);
+                match env::args().nth(3) {
+                    Some(path) => {
+                        fs::write(path, rendered).expect("Should be able to write the file")
+                    }
+                    None => print!("{}", rendered),
+                }
             }
+            _ => panic!("Unknown output format {}", format),
         }
     }
-
-    None
 }
 
-fn get_next_positions(current_position: Coordinate, valley: &Valley) -> Vec<Coordinate> {
+fn get_next_positions(
+    current_position: Coordinate,
+    forecast: &Forecast,
+    time: u32,
+) -> Vec<Coordinate> {
     let mut next_positions = Vec::new();
 
     if current_position.x > 0 {
@@ -482,7 +511,7 @@ fn get_next_positions(current_position: Coordinate, valley: &Valley) -> Vec<Coor
         next_positions.push(up);
     }
 
-    if current_position.x < valley.get_width() - 1 {
+    if current_position.x < forecast.get_width() - 1 {
         let right = Coordinate {
             x: current_position.x + 1,
             y: current_position.y,
@@ -490,7 +519,7 @@ fn get_next_positions(current_position: Coordinate, valley: &Valley) -> Vec<Coor
         next_positions.push(right);
     }
 
-    if current_position.y < valley.get_height() - 1 {
+    if current_position.y < forecast.get_height() - 1 {
         let down = Coordinate {
             x: current_position.x,
             y: current_position.y + 1,
@@ -503,10 +532,7 @@ fn get_next_positions(current_position: Coordinate, valley: &Valley) -> Vec<Coor
 
     next_positions
         .into_iter()
-        .filter(|position| match valley.get_part(*position) {
-            ValleyPart::ClearGround => true,
-            _ => false,
-        })
+        .filter(|position| forecast.is_clear(*position, time))
         .collect()
 }
 
@@ -514,3 +540,246 @@ fn load_input() -> String {
     let args: Vec<String> = env::args().collect();
     fs::read_to_string(args.get(1).unwrap()).expect("Should have been able to read the file")
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{
+        forecast::Forecast,
+        itinerary::find_itinerary,
+        route::{render_route, Move, Route},
+        Blizzard, Coordinate, Direction, ParseValleyError, Valley, ValleyIterator, DIRECTIONS,
+    };
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn find_quickest_route(
+        start: Coordinate,
+        end: Coordinate,
+        forecast: &Forecast,
+        start_time: u32,
+    ) -> Option<Route> {
+        find_itinerary(forecast, &[start, end], start_time)
+            .ok()
+            .map(|mut legs| legs.remove(0))
+    }
+
+    #[test]
+    fn test_forecast() {
+        init();
+        let valley = Valley::from_str(include_str!("../test_2.txt")).unwrap();
+        let forecast = Forecast::new(&valley);
+
+        assert_eq!(12, forecast.get_period());
+        assert!(!forecast.is_clear(Coordinate { x: 1, y: 1 }, 0));
+        assert!(forecast.is_clear(Coordinate { x: 1, y: 1 }, 1));
+        assert!(!forecast.is_clear(Coordinate { x: 1, y: 1 }, 12));
+        assert!(forecast.is_clear(Coordinate { x: 1, y: 0 }, 5));
+        assert!(!forecast.is_clear(Coordinate { x: 0, y: 0 }, 5));
+    }
+
+    #[test]
+    fn test_there_and_back_again() {
+        init();
+        let valley = Valley::from_str(include_str!("../test_2.txt")).unwrap();
+        let forecast = Forecast::new(&valley);
+        let start = Coordinate { x: 1, y: 0 };
+        let end = Coordinate { x: 6, y: 5 };
+
+        let end_time = |start, end, start_time| {
+            find_quickest_route(start, end, &forecast, start_time).map(|route| route.end_time())
+        };
+
+        assert_eq!(Some(18), end_time(start, end, 0));
+        assert_eq!(Some(18 + 23), end_time(end, start, 18));
+        assert_eq!(Some(54), end_time(start, end, 41));
+    }
+
+    #[test]
+    fn test_unreachable() {
+        init();
+        let valley = Valley::from_str("#.###\n#...#\n###.#").unwrap();
+        let forecast = Forecast::new(&valley);
+        let start = Coordinate { x: 1, y: 0 };
+
+        let goal = find_quickest_route(start, Coordinate { x: 3, y: 2 }, &forecast, 0);
+        assert_eq!(Some(4), goal.map(|route| route.end_time()));
+
+        let wall = find_quickest_route(start, Coordinate { x: 4, y: 1 }, &forecast, 0);
+        assert_eq!(None, wall);
+    }
+
+    #[test]
+    fn test_route_moves() {
+        init();
+        let valley = Valley::from_str(include_str!("../test_2.txt")).unwrap();
+        let forecast = Forecast::new(&valley);
+        let start = Coordinate { x: 1, y: 0 };
+        let end = Coordinate { x: 6, y: 5 };
+
+        let route = find_quickest_route(start, end, &forecast, 0).unwrap();
+        assert_eq!(18, route.moves.len());
+        assert_eq!(Some(&end), route.positions().last());
+
+        for (minute, position) in route.positions().into_iter().enumerate() {
+            assert!(forecast.is_clear(position, minute as u32));
+        }
+
+        let expected = "Initial state:
+#E######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#
+
+Minute 1, move down:
+#.######
+#E>3.<.#
+#<..<<.#
+#>2.22.#
+#>v..^<#
+######.#
+";
+        let first_minute = Route {
+            moves: route.moves[..1].to_vec(),
+            ..route.clone()
+        };
+        assert_eq!(Move::Down, route.moves[0]);
+        assert_eq!(expected, render_route(&valley, &first_minute));
+
+        let rendered = render_route(&valley, &route);
+        assert!(rendered.ends_with(
+            "\nMinute 18, move down:\n#.######\n#>2.<.<#\n#.2v^2<#\n#>..>2>#\n#<....>#\n######E#\n"
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        init();
+        let valley = Valley::from_str(include_str!("../test_2.txt")).unwrap();
+
+        for valley in (ValleyIterator { valley }).take(12) {
+            let text = format!("{:#}", valley);
+            let parsed = Valley::from_str(&text).unwrap();
+
+            assert_eq!(text, format!("{:#}", parsed));
+            assert_eq!(valley.get_occupancy(), parsed.get_occupancy());
+        }
+
+        let valley = Valley::from_str("#.####\n#3.<.#\n#.↗..#\n####.#\n\n1,1 v<↗").unwrap();
+        assert_eq!(5, valley.get_blizzards().len());
+        assert_eq!("\n#.####\n#3.<.#\n#.↗..#\n####.#\n", valley.to_string());
+        assert_eq!(
+            "\n#.####\n#3.<.#\n#.↗..#\n####.#\n\n1,1 v<↗\n",
+            format!("{:#}", valley)
+        );
+
+        let next = ValleyIterator { valley }.next().unwrap();
+        assert_eq!("\n#.####\n#.<↗<#\n#v↗..#\n####.#\n", next.to_string());
+
+        assert_eq!(Err(ParseValleyError), Valley::from_str("#.##\n#x.#\n##.#"));
+        assert_eq!(Err(ParseValleyError), Valley::from_str("#>##\n#..#\n##.#"));
+        assert_eq!(Err(ParseValleyError), Valley::from_str("#.##\n#..\n##.#"));
+    }
+
+    #[test]
+    fn test_counts_need_directions() {
+        init();
+        let valley = "#.####\n#3.<.#\n#.↗..#\n####.#";
+
+        // Only the alternate form round-trips once blizzards overlap: the plain form writes the
+        // count without saying which ways the blizzards blow.
+        let overlapping = Valley::from_str(&format!("{}\n\n1,1 v<↗", valley)).unwrap();
+        assert_eq!(
+            Err(ParseValleyError),
+            Valley::from_str(&overlapping.to_string())
+        );
+        assert!(Valley::from_str(&format!("{:#}", overlapping)).is_ok());
+
+        assert_eq!(Err(ParseValleyError), Valley::from_str(valley));
+        assert_eq!(
+            Err(ParseValleyError),
+            Valley::from_str(&format!("{}\n\n1,1 v<", valley))
+        );
+        assert_eq!(
+            Err(ParseValleyError),
+            Valley::from_str(&format!("{}\n\n1,1 v<↗\n2,1 <>", valley))
+        );
+        assert_eq!(
+            Err(ParseValleyError),
+            Valley::from_str(&format!("{}\n\n1,1 v<x", valley))
+        );
+        assert_eq!(
+            Err(ParseValleyError),
+            Valley::from_str("#.####\n#1.<.#\n#.↗..#\n####.#\n\n1,1 v")
+        );
+    }
+
+    #[test]
+    fn test_resume_from_rendered_minute() {
+        init();
+        let valley = Valley::from_str(include_str!("../test_2.txt")).unwrap();
+        let minute_7 = (ValleyIterator { valley }).nth(6).unwrap();
+        assert!(minute_7.to_string().contains('2'));
+
+        let resumed = Valley::from_str(&format!("{:#}", minute_7)).unwrap();
+        let expected: Vec<String> = (ValleyIterator { valley: minute_7 })
+            .take(24)
+            .map(|valley| format!("{:#}", valley))
+            .collect();
+        let actual: Vec<String> = (ValleyIterator { valley: resumed })
+            .take(24)
+            .map(|valley| format!("{:#}", valley))
+            .collect();
+        assert_eq!(expected, actual);
+
+        let mut crowded = Valley::from_str("#.####\n#....#\n#....#\n####.#").unwrap();
+        crowded.set_blizzards(
+            (0..12)
+                .map(|id| Blizzard {
+                    id,
+                    position: Coordinate { x: 2, y: 1 },
+                    velocity: DIRECTIONS[id as usize % 8].0,
+                })
+                .collect(),
+        );
+        let text = format!("{:#}", crowded);
+        assert!(text.starts_with("\n#.####\n#.*..#\n"));
+
+        let parsed = Valley::from_str(&text).unwrap();
+        assert_eq!(text, format!("{:#}", parsed));
+        assert_eq!(
+            format!("{:#}", ValleyIterator { valley: crowded }.next().unwrap()),
+            format!("{:#}", ValleyIterator { valley: parsed }.next().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_diagonal_blizzards() {
+        init();
+        let mut valley = Valley::from_str("#.####\n#....#\n#....#\n####.#").unwrap();
+        valley.set_blizzards(
+            (0..5)
+                .map(|id| Blizzard {
+                    id,
+                    position: Coordinate { x: 4, y: 2 },
+                    velocity: Direction::DownRight,
+                })
+                .collect(),
+        );
+        assert_eq!("\n#.####\n#....#\n#...5#\n####.#\n", valley.to_string());
+
+        let valleys: Vec<String> = ValleyIterator { valley }
+            .take(4)
+            .map(|valley| valley.to_string())
+            .collect();
+        assert_eq!("\n#.####\n#5...#\n#....#\n####.#\n", valleys[0]);
+        assert_eq!("\n#.####\n#....#\n#.5..#\n####.#\n", valleys[1]);
+        assert_eq!("\n#.####\n#..5.#\n#....#\n####.#\n", valleys[2]);
+        assert_eq!("\n#.####\n#....#\n#...5#\n####.#\n", valleys[3]);
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt::{Display, Write};
+
+use crate::{Coordinate, Valley, ValleyIterator};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+    Wait,
+}
+
+impl Move {
+    pub fn between(from: Coordinate, to: Coordinate) -> Move {
+        match (to.x as i64 - from.x as i64, to.y as i64 - from.y as i64) {
+            (0, -1) => Move::Up,
+            (0, 1) => Move::Down,
+            (-1, 0) => Move::Left,
+            (1, 0) => Move::Right,
+            (0, 0) => Move::Wait,
+            _ => panic!("{:?} and {:?} are not adjacent", from, to),
+        }
+    }
+
+    fn apply(&self, position: Coordinate) -> Coordinate {
+        let Coordinate { x, y } = position;
+
+        match self {
+            Move::Up => Coordinate { x, y: y - 1 },
+            Move::Down => Coordinate { x, y: y + 1 },
+            Move::Left => Coordinate { x: x - 1, y },
+            Move::Right => Coordinate { x: x + 1, y },
+            Move::Wait => position,
+        }
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Up => f.write_str("move up"),
+            Move::Down => f.write_str("move down"),
+            Move::Left => f.write_str("move left"),
+            Move::Right => f.write_str("move right"),
+            Move::Wait => f.write_str("wait"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub start: Coordinate,
+    pub start_time: u32,
+    pub moves: Vec<Move>,
+}
+
+impl Route {
+    pub fn end_time(&self) -> u32 {
+        self.start_time + self.moves.len() as u32
+    }
+
+    pub fn positions(&self) -> Vec<Coordinate> {
+        let mut positions = vec![self.start];
+
+        for step in self.moves.iter() {
+            positions.push(step.apply(*positions.last().unwrap()));
+        }
+
+        positions
+    }
+
+    // Carries on with a route that starts where and when this one finishes.
+    pub fn append(&mut self, next: Route) {
+        assert_eq!(self.end_time(), next.start_time);
+        assert_eq!(self.positions().last(), Some(&next.start));
+
+        self.moves.extend(next.moves);
+    }
+}
+
+fn overlay_expedition(valley: &Valley, position: Coordinate) -> String {
+    let mut rows: Vec<Vec<char>> = valley
+        .to_string()
+        .trim_start_matches('\n')
+        .lines()
+        .map(|row| row.chars().collect())
+        .collect();
+
+    rows[position.y][position.x] = 'E';
+
+    rows.into_iter()
+        .map(|row| row.into_iter().collect::<String>() + "\n")
+        .collect()
+}
+
+// Draws the valley every minute of the route, starting from the valley at minute zero.
+pub fn render_route(valley: &Valley, route: &Route) -> String {
+    let mut valleys = std::iter::once(valley.clone()).chain(ValleyIterator {
+        valley: valley.clone(),
+    });
+    let mut valley = valleys.nth(route.start_time as usize).unwrap();
+
+    let positions = route.positions();
+    let mut output = String::new();
+
+    writeln!(output, "Initial state:").unwrap();
+    output.push_str(&overlay_expedition(&valley, positions[0]));
+
+    for (i, (step, position)) in route.moves.iter().zip(positions.iter().skip(1)).enumerate() {
+        valley = valleys.next().unwrap();
+
+        writeln!(
+            output,
+            "\nMinute {}, {}:",
+            route.start_time as usize + i + 1,
+            step
+        )
+        .unwrap();
+        output.push_str(&overlay_expedition(&valley, *position));
+    }
+
+    output
+}
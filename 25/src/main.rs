@@ -1,142 +1,240 @@
-use std::{
-    env,
-    fmt::{Display, Error, Write},
-    fs,
-    iter::Sum,
-    ops::Add,
-    str::FromStr,
-};
-
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-struct SNAFU {
-    value: i64,
+mod positional;
+mod snafu;
+#[cfg(test)]
+mod testing;
+
+use num_bigint::BigInt;
+use positional::{BalancedTernary, Binary, Decimal, Hexadecimal, ParseNumberError};
+use snafu::{parse_snafu_line, ParseSNAFUError, SNAFU};
+use std::{env, fmt::Display, fs, process};
+
+const SYSTEMS: &str = "snafu, balanced-ternary, binary, decimal or hexadecimal";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum System {
+    Snafu,
+    BalancedTernary,
+    Binary,
+    Decimal,
+    Hexadecimal,
 }
 
-impl SNAFU {
-    const BASE: i64 = 5;
+impl System {
+    fn from_name(name: &str) -> Option<System> {
+        match name {
+            "snafu" => Some(System::Snafu),
+            "balanced-ternary" => Some(System::BalancedTernary),
+            "binary" => Some(System::Binary),
+            "decimal" => Some(System::Decimal),
+            "hexadecimal" => Some(System::Hexadecimal),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug)]
-struct ParseSNAFUError;
-
-impl FromStr for SNAFU {
-    type Err = ParseSNAFUError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let powers = (0..).map(|exponent| SNAFU::BASE.pow(exponent));
-
-        let value = s
-            .chars()
-            .map(|digit| match digit {
-                '2' => Ok(2),
-                '1' => Ok(1),
-                '0' => Ok(0),
-                '-' => Ok(-1),
-                '=' => Ok(-2),
-                _ => Err(ParseSNAFUError),
-            })
-            .rev()
-            .zip(powers)
-            .map(|(base, exponent)| base.map(|b| b * exponent))
-            .sum::<Result<i64, ParseSNAFUError>>()?;
+fn read_number(system: System, number: &str) -> Result<BigInt, ParseNumberError> {
+    match system {
+        System::Snafu => number.parse::<SNAFU>().map(|n| BigInt::from(&n)),
+        System::BalancedTernary => number.parse::<BalancedTernary>().map(|n| BigInt::from(&n)),
+        System::Binary => number.parse::<Binary>().map(|n| BigInt::from(&n)),
+        System::Decimal => number.parse::<Decimal>().map(|n| BigInt::from(&n)),
+        System::Hexadecimal => number.parse::<Hexadecimal>().map(|n| BigInt::from(&n)),
+    }
+}
 
-        Ok(SNAFU { value })
+fn write_number(system: System, value: &BigInt) -> String {
+    match system {
+        System::Snafu => SNAFU::from(value).to_string(),
+        System::BalancedTernary => BalancedTernary::from(value).to_string(),
+        System::Binary => Binary::from(value).to_string(),
+        System::Decimal => Decimal::from(value).to_string(),
+        System::Hexadecimal => Hexadecimal::from(value).to_string(),
     }
 }
 
-impl Display for SNAFU {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut remainders = Vec::new();
-        let mut quotient = self.value;
+fn exit_with_usage() -> ! {
+    eprintln!(
+        "Usage: convert <from> <to> <number>..., where the systems are {}",
+        SYSTEMS
+    );
+    process::exit(1);
+}
 
-        while quotient != 0 {
-            let remainder = quotient % SNAFU::BASE;
+// convert <from> <to> <number>...
+fn convert(args: &[String]) {
+    let (from, to) = match args {
+        [from, to, ..] => (from, to),
+        _ => exit_with_usage(),
+    };
+    let (from_system, to_system) = match (System::from_name(from), System::from_name(to)) {
+        (Some(from_system), Some(to_system)) => (from_system, to_system),
+        _ => exit_with_usage(),
+    };
+
+    for number in args[2..].iter() {
+        let value = read_number(from_system, number).unwrap_or_else(|error| {
+            match error.character {
+                Some(character) => eprintln!(
+                    "{:?}, column {}: {:?} is not a {} digit",
+                    number, error.column, character, from
+                ),
+                None => eprintln!(
+                    "{:?}, column {}: expected a {} digit",
+                    number, error.column, from
+                ),
+            }
+            process::exit(1);
+        });
 
-            quotient = quotient / SNAFU::BASE;
+        println!("{}", write_number(to_system, &value));
+    }
+}
 
-            remainders.push(remainder);
-        }
+fn main() {
+    env_logger::init();
 
-        log::debug!("{:?}", remainders);
+    let args: Vec<String> = env::args().collect();
 
-        let mut i = 0;
-        while i < remainders.len() {
-            if remainders[i] > 2 {
-                remainders[i] = remainders[i] - SNAFU::BASE;
+    match args.get(1).map(String::as_str) {
+        Some("convert") => return convert(&args[2..]),
+        Some("validate") => return validate(&args[2..]),
+        _ => {}
+    }
 
-                if i == remainders.len() - 1 {
-                    remainders.push(1);
-                } else {
-                    remainders[i + 1] += 1;
-                }
+    let input = load_input();
+    let fuel_requirements: Vec<SNAFU> = match load_output_format().as_deref() {
+        None => parse_fuel_requirements(&input).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        }),
+        Some("--all-errors") => parse_all_fuel_requirements(&input).unwrap_or_else(|errors| {
+            for error in errors {
+                eprintln!("{}", error);
             }
+            process::exit(1);
+        }),
+        Some(format) => panic!("Unknown output format {}", format),
+    };
+    let total_fuel_requirements: SNAFU = fuel_requirements.into_iter().sum();
 
-            i = i + 1;
-        }
+    println!("{}", total_fuel_requirements);
+}
 
-        remainders.reverse();
+fn load_output_format() -> Option<String> {
+    env::args().nth(2)
+}
 
-        log::debug!("{:?}", remainders);
+fn load_input() -> String {
+    let args: Vec<String> = env::args().collect();
+    fs::read_to_string(args.get(1).unwrap()).expect("Should have been able to read the file")
+}
 
-        for remainder in remainders.into_iter() {
-            let c = match remainder {
-                2 => Ok('2'),
-                1 => Ok('1'),
-                0 => Ok('0'),
-                -1 => Ok('-'),
-                -2 => Ok('='),
-                _ => Err(Error::default()),
-            }?;
+// Numbered from one, skipping blank lines.
+fn get_lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .split('\n')
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(_, line)| line != &"")
+}
 
-            f.write_char(c)?;
-        }
+fn parse_fuel_requirements(input: &str) -> Result<Vec<SNAFU>, ParseSNAFUError> {
+    get_lines(input)
+        .map(|(line, snafu)| parse_snafu_line(line, snafu))
+        .collect()
+}
 
-        Ok(())
+// Like `parse_fuel_requirements`, but carries on past a bad line to report every one.
+fn parse_all_fuel_requirements(input: &str) -> Result<Vec<SNAFU>, Vec<ParseSNAFUError>> {
+    let (fuel_requirements, errors): (Vec<_>, Vec<_>) = get_lines(input)
+        .map(|(line, snafu)| parse_snafu_line(line, snafu))
+        .partition(Result::is_ok);
+
+    match errors.is_empty() {
+        true => Ok(fuel_requirements.into_iter().map(Result::unwrap).collect()),
+        false => Err(errors.into_iter().map(Result::unwrap_err).collect()),
     }
 }
 
-impl Add for SNAFU {
-    type Output = SNAFU;
+#[derive(Debug, PartialEq, Eq)]
+enum Issue {
+    Invalid(ParseSNAFUError),
+    NonCanonical { line: usize, normalised: SNAFU },
+}
 
-    fn add(self, rhs: Self) -> Self::Output {
-        SNAFU {
-            value: self.value + rhs.value,
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::Invalid(error) => write!(f, "{}", error),
+            Issue::NonCanonical { line, normalised } => {
+                write!(f, "line {}: should be written {}", line, normalised)
+            }
         }
     }
 }
 
-impl Sum for SNAFU {
-    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
-        iter.fold(SNAFU { value: 0 }, |a, b| a + b)
-    }
-}
+// Every line that isn't SNAFU, or isn't written the one way `Display` writes it, e.g. with
+// leading zeros. Also returns the input with every readable number normalised.
+fn lint_fuel_requirements(input: &str) -> (Vec<Issue>, String) {
+    let mut issues = Vec::new();
 
-fn main() {
-    env_logger::init();
+    let normalised = input
+        .split('\n')
+        .enumerate()
+        .map(|(i, text)| match parse_snafu_line(i + 1, text) {
+            _ if text.is_empty() => text.to_string(),
+            Ok(snafu) => {
+                let canonical = snafu.to_string();
+
+                if canonical != text {
+                    issues.push(Issue::NonCanonical {
+                        line: i + 1,
+                        normalised: snafu,
+                    });
+                }
 
-    let input = load_input();
-    let fuel_requirements: Vec<SNAFU> = parse_fuel_requirements(&input).unwrap();
-    let total_fuel_requirements: SNAFU = fuel_requirements.iter().cloned().sum();
+                canonical
+            }
+            Err(error) => {
+                issues.push(Issue::Invalid(error));
+                text.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
 
-    println!("{}", total_fuel_requirements);
+    (issues, normalised)
 }
 
-fn load_input() -> String {
-    let args: Vec<String> = env::args().collect();
-    fs::read_to_string(args.get(1).unwrap()).expect("Should have been able to read the file")
-}
+// validate <file> [normalised file]
+fn validate(args: &[String]) {
+    let path = args
+        .first()
+        .expect("Usage: validate <file> [normalised file]");
+    let input = fs::read_to_string(path).expect("Should have been able to read the file");
 
-fn parse_fuel_requirements(input: &str) -> Result<Vec<SNAFU>, ParseSNAFUError> {
-    input
-        .split('\n')
-        .filter(|line| line != &"")
-        .map(|snafu| snafu.parse())
-        .collect()
+    let (issues, normalised) = lint_fuel_requirements(&input);
+
+    for issue in issues.iter() {
+        println!("{}: {}", path, issue);
+    }
+
+    if let Some(output) = args.get(1) {
+        fs::write(output, normalised).expect("Should be able to write the file");
+    }
+
+    if !issues.is_empty() {
+        process::exit(1);
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::SNAFU;
+    use crate::{
+        lint_fuel_requirements, parse_all_fuel_requirements, parse_fuel_requirements,
+        positional::ParseNumberError, read_number, snafu::ParseSNAFUError, write_number, Issue,
+        System, SNAFU,
+    };
 
     fn init() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -147,7 +245,7 @@ mod tests {
         init();
 
         let input = "2=-01";
-        let expected = SNAFU { value: 976 };
+        let expected = SNAFU::from(976);
 
         let actual: SNAFU = input.parse().unwrap();
 
@@ -158,29 +256,140 @@ mod tests {
     fn test_display_snafu() {
         init();
 
-        const TESTS: &'static [(SNAFU, &str)] = &[
-            (SNAFU { value: 1 }, "1"),
-            (SNAFU { value: 2 }, "2"),
-            (SNAFU { value: 3 }, "1="),
-            (SNAFU { value: 4 }, "1-"),
-            (SNAFU { value: 5 }, "10"),
-            (SNAFU { value: 6 }, "11"),
-            (SNAFU { value: 7 }, "12"),
-            (SNAFU { value: 8 }, "2="),
-            (SNAFU { value: 9 }, "2-"),
-            (SNAFU { value: 10 }, "20"),
-            (SNAFU { value: 15 }, "1=0"),
-            (SNAFU { value: 20 }, "1-0"),
-            (SNAFU { value: 2022 }, "1=11-2"),
-            (SNAFU { value: 12345 }, "1-0---0"),
-            (SNAFU { value: 314159265 }, "1121-1110-1=0"),
-            (SNAFU { value: 4890 }, "2=-1=0"),
+        const TESTS: &[(i128, &str)] = &[
+            (1, "1"),
+            (2, "2"),
+            (3, "1="),
+            (4, "1-"),
+            (5, "10"),
+            (6, "11"),
+            (7, "12"),
+            (8, "2="),
+            (9, "2-"),
+            (10, "20"),
+            (15, "1=0"),
+            (20, "1-0"),
+            (2022, "1=11-2"),
+            (12345, "1-0---0"),
+            (314159265, "1121-1110-1=0"),
+            (4890, "2=-1=0"),
         ];
 
         for (input, expected) in TESTS {
-            let actual = input.to_string();
+            let actual = SNAFU::from(*input).to_string();
 
             assert_eq!(expected.to_string(), actual);
         }
     }
+
+    #[test]
+    fn test_total_fuel_requirements() {
+        init();
+
+        let fuel_requirements = parse_fuel_requirements(include_str!("../test.txt")).unwrap();
+        let total: SNAFU = fuel_requirements.into_iter().sum();
+
+        assert_eq!("2=-1=0", total.to_string());
+        assert_eq!(Ok(4890), i128::try_from(&total));
+    }
+
+    #[test]
+    fn test_convert_between_systems() {
+        init();
+
+        let value = read_number(System::Snafu, "2=-01").unwrap();
+
+        assert_eq!("976", write_number(System::Decimal, &value));
+        assert_eq!("3d0", write_number(System::Hexadecimal, &value));
+        assert_eq!("1111010000", write_number(System::Binary, &value));
+        assert_eq!("++000++", write_number(System::BalancedTernary, &value));
+
+        let negative = read_number(System::Decimal, "-976").unwrap();
+        assert_eq!("=210-", write_number(System::Snafu, &negative));
+        assert_eq!("-1111010000", write_number(System::Binary, &negative));
+        assert_eq!(
+            Err(ParseNumberError {
+                column: 3,
+                character: Some('2')
+            }),
+            read_number(System::Binary, "102").map(|_| ())
+        );
+
+        assert_eq!(
+            Some(System::BalancedTernary),
+            System::from_name("balanced-ternary")
+        );
+        assert_eq!(None, System::from_name("roman"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        init();
+
+        let input = "1=\n\n12x\n2=\n-3-a\n";
+
+        assert_eq!(
+            Err(ParseSNAFUError {
+                line: 3,
+                column: 3,
+                character: Some('x')
+            }),
+            parse_fuel_requirements(input)
+        );
+        assert_eq!(
+            Err(vec![
+                ParseSNAFUError {
+                    line: 3,
+                    column: 3,
+                    character: Some('x')
+                },
+                ParseSNAFUError {
+                    line: 5,
+                    column: 2,
+                    character: Some('3')
+                }
+            ]),
+            parse_all_fuel_requirements(input)
+        );
+        assert_eq!(
+            "line 5, column 2: '3' is not a SNAFU digit",
+            parse_all_fuel_requirements(input).unwrap_err()[1].to_string()
+        );
+        assert_eq!(
+            Ok(13),
+            parse_all_fuel_requirements(include_str!("../test.txt")).map(|numbers| numbers.len())
+        );
+    }
+
+    #[test]
+    fn test_lint() {
+        init();
+
+        let (issues, normalised) = lint_fuel_requirements("1=\n002=\n0\n00\n1?\n");
+
+        assert_eq!(
+            vec![
+                Issue::NonCanonical {
+                    line: 2,
+                    normalised: "2=".parse().unwrap()
+                },
+                Issue::NonCanonical {
+                    line: 4,
+                    normalised: SNAFU::default()
+                },
+                Issue::Invalid(ParseSNAFUError {
+                    line: 5,
+                    column: 2,
+                    character: Some('?')
+                }),
+            ],
+            issues
+        );
+        assert_eq!("line 2: should be written 2=", issues[0].to_string());
+        assert_eq!("1=\n2=\n0\n0\n1?\n", normalised);
+
+        let (issues, normalised) = lint_fuel_requirements(include_str!("../test.txt"));
+        assert!(issues.is_empty());
+        assert_eq!(include_str!("../test.txt"), normalised);
+    }
 }
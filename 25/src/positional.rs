@@ -0,0 +1,555 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Display, Write},
+    iter::Sum,
+    marker::PhantomData,
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+use num_bigint::BigInt;
+
+// The characters a numeral system writes its digits with, from the lowest digit value up.
+// A standard system's digits run from zero and negative numbers get a leading '-', while a
+// balanced system's digits are centred on zero so it needs no sign.
+pub trait Digits {
+    const ALPHABET: &'static [char];
+    const BALANCED: bool;
+}
+
+// Digits are least significant first with no zeros at the most significant end, so every
+// number has exactly one representation and zero has no digits at all. A negative number
+// in a standard system has every digit negated.
+pub struct PositionalNumber<const BASE: u32, D: Digits> {
+    digits: Vec<i8>,
+    system: PhantomData<D>,
+}
+
+// Where reading a number went wrong, counting columns from one. No character means the
+// number ended where a digit was expected.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseNumberError {
+    pub column: usize,
+    pub character: Option<char>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NumberOverflowError;
+
+impl<const BASE: u32, D: Digits> PositionalNumber<BASE, D> {
+    const VALID: () = assert!(
+        D::ALPHABET.len() == BASE as usize && BASE >= 2 && BASE <= 127,
+        "A numeral system needs one character per digit"
+    );
+
+    const LOWEST: i64 = match D::BALANCED {
+        true => -(BASE as i64 - 1) / 2,
+        false => 0,
+    };
+
+    const HIGHEST: i64 = Self::LOWEST + BASE as i64 - 1;
+
+    fn new(digits: Vec<i8>) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID;
+
+        PositionalNumber {
+            digits,
+            system: PhantomData,
+        }
+    }
+
+    // Splits a value into its lowest digit and what carries into the next column.
+    fn balance(value: i64) -> (i8, i64) {
+        let (mut quotient, mut remainder) =
+            (value.div_euclid(BASE as i64), value.rem_euclid(BASE as i64));
+
+        if remainder > Self::HIGHEST {
+            remainder -= BASE as i64;
+            quotient += 1;
+        }
+
+        (remainder as i8, quotient)
+    }
+
+    // Carries any column outside the digit range into the next one up. Standard digits
+    // can't hold a negative number, which shows up as a carry below zero off the top, so
+    // that is normalised as the negation of its opposite.
+    fn from_columns(columns: Vec<i64>) -> Self {
+        let mut digits = Vec::with_capacity(columns.len() + 1);
+        let mut carry = 0;
+
+        for &column in columns.iter() {
+            let (digit, next_carry) = Self::balance(column + carry);
+            digits.push(digit);
+            carry = next_carry;
+        }
+
+        if carry < 0 && !D::BALANCED {
+            return -Self::from_columns(columns.into_iter().map(|column| -column).collect());
+        }
+
+        while carry != 0 {
+            let (digit, next_carry) = Self::balance(carry);
+            digits.push(digit);
+            carry = next_carry;
+        }
+
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        Self::new(digits)
+    }
+
+    // Only used for standard systems, whose digits of a magnitude are its plain remainders.
+    fn from_magnitude(value: u128) -> Self {
+        let mut digits = Vec::new();
+        let mut quotient = value;
+
+        while quotient != 0 {
+            digits.push((quotient % BASE as u128) as i8);
+            quotient /= BASE as u128;
+        }
+
+        Self::new(digits)
+    }
+
+    fn signum(&self) -> i8 {
+        self.digits.last().map_or(0, |digit| digit.signum())
+    }
+
+    fn is_negative(&self) -> bool {
+        self.signum() < 0
+    }
+}
+
+impl<const BASE: u32, D: Digits> Clone for PositionalNumber<BASE, D> {
+    fn clone(&self) -> Self {
+        Self::new(self.digits.clone())
+    }
+}
+
+impl<const BASE: u32, D: Digits> Default for PositionalNumber<BASE, D> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<const BASE: u32, D: Digits> PartialEq for PositionalNumber<BASE, D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.digits == other.digits
+    }
+}
+
+impl<const BASE: u32, D: Digits> Eq for PositionalNumber<BASE, D> {}
+
+impl<const BASE: u32, D: Digits> Debug for PositionalNumber<BASE, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl<const BASE: u32, D: Digits> FromStr for PositionalNumber<BASE, D> {
+    type Err = ParseNumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, s) = match (D::BALANCED, s.strip_prefix('-')) {
+            (false, Some(magnitude)) => (true, magnitude),
+            _ => (false, s),
+        };
+        let first_column = if negative { 2 } else { 1 };
+
+        if s.is_empty() {
+            return Err(ParseNumberError {
+                column: first_column,
+                character: None,
+            });
+        }
+
+        let mut columns = s
+            .chars()
+            .enumerate()
+            .map(|(i, digit)| {
+                D::ALPHABET
+                    .iter()
+                    .position(|&c| c == digit)
+                    .map(|position| position as i64 + Self::LOWEST)
+                    .ok_or(ParseNumberError {
+                        column: first_column + i,
+                        character: Some(digit),
+                    })
+            })
+            .collect::<Result<Vec<i64>, ParseNumberError>>()?;
+        columns.reverse();
+
+        let number = Self::from_columns(columns);
+
+        Ok(match negative {
+            true => -number,
+            false => number,
+        })
+    }
+}
+
+impl<const BASE: u32, D: Digits> Display for PositionalNumber<BASE, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let glyph = |digit: i64| D::ALPHABET[(digit - Self::LOWEST) as usize];
+
+        if self.digits.is_empty() {
+            return f.write_char(glyph(0));
+        }
+
+        let sign = match self.is_negative() && !D::BALANCED {
+            true => {
+                f.write_char('-')?;
+                -1
+            }
+            false => 1,
+        };
+
+        for &digit in self.digits.iter().rev() {
+            f.write_char(glyph(digit as i64 * sign))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<const BASE: u32, D: Digits> From<i128> for PositionalNumber<BASE, D> {
+    fn from(value: i128) -> Self {
+        if value < 0 && !D::BALANCED {
+            return -Self::from_magnitude(value.unsigned_abs());
+        }
+
+        let mut digits = Vec::new();
+        let mut quotient = value;
+
+        while quotient != 0 {
+            let mut remainder = quotient.rem_euclid(BASE as i128);
+            quotient = quotient.div_euclid(BASE as i128);
+
+            if remainder > Self::HIGHEST as i128 {
+                remainder -= BASE as i128;
+                quotient += 1;
+            }
+
+            digits.push(remainder as i8);
+        }
+
+        Self::new(digits)
+    }
+}
+
+impl<const BASE: u32, D: Digits> TryFrom<&PositionalNumber<BASE, D>> for i128 {
+    type Error = NumberOverflowError;
+
+    // Multiplying by the base before adding the digit would overflow on the way to
+    // i128::MIN, so each step takes one less than the base times the value so far, adds the
+    // digit and then the value.
+    fn try_from(number: &PositionalNumber<BASE, D>) -> Result<Self, Self::Error> {
+        number.digits.iter().rev().try_fold(0i128, |value, &digit| {
+            value
+                .checked_mul(BASE as i128 - 1)
+                .and_then(|partial| partial.checked_add(digit as i128))
+                .and_then(|partial| partial.checked_add(value))
+                .ok_or(NumberOverflowError)
+        })
+    }
+}
+
+impl<const BASE: u32, D: Digits> From<&BigInt> for PositionalNumber<BASE, D> {
+    fn from(value: &BigInt) -> Self {
+        if *value < BigInt::ZERO && !D::BALANCED {
+            return -Self::from(&-value);
+        }
+
+        let base = BigInt::from(BASE);
+        let mut digits = Vec::new();
+        let mut quotient = value.clone();
+
+        while quotient != BigInt::ZERO {
+            let remainder = i64::try_from(((&quotient % &base) + &base) % &base).unwrap();
+            let (digit, carry) = Self::balance(remainder);
+
+            quotient = (quotient - remainder) / &base + carry;
+            digits.push(digit);
+        }
+
+        Self::new(digits)
+    }
+}
+
+impl<const BASE: u32, D: Digits> From<&PositionalNumber<BASE, D>> for BigInt {
+    fn from(number: &PositionalNumber<BASE, D>) -> Self {
+        number
+            .digits
+            .iter()
+            .rev()
+            .fold(BigInt::ZERO, |value, &digit| value * BASE + digit)
+    }
+}
+
+impl<const BASE: u32, D: Digits> Neg for PositionalNumber<BASE, D> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(self.digits.into_iter().map(|digit| -digit).collect())
+    }
+}
+
+// Adds column by column in the number's own base, carrying as it goes.
+impl<const BASE: u32, D: Digits> Add for PositionalNumber<BASE, D> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let length = self.digits.len().max(rhs.digits.len());
+        let digit = |number: &Self, i: usize| *number.digits.get(i).unwrap_or(&0) as i64;
+
+        Self::from_columns(
+            (0..length)
+                .map(|i| digit(&self, i) + digit(&rhs, i))
+                .collect(),
+        )
+    }
+}
+
+impl<const BASE: u32, D: Digits> Sub for PositionalNumber<BASE, D> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+// Long multiplication: each column collects the products of the digit pairs that land in
+// it, and the carries are settled at the end.
+impl<const BASE: u32, D: Digits> Mul for PositionalNumber<BASE, D> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        if self.digits.is_empty() || rhs.digits.is_empty() {
+            return Self::default();
+        }
+
+        let mut columns = vec![0; self.digits.len() + rhs.digits.len() - 1];
+
+        for (i, &a) in self.digits.iter().enumerate() {
+            for (j, &b) in rhs.digits.iter().enumerate() {
+                columns[i + j] += a as i64 * b as i64;
+            }
+        }
+
+        Self::from_columns(columns)
+    }
+}
+
+impl<const BASE: u32, D: Digits> Ord for PositionalNumber<BASE, D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.clone() - other.clone()).signum().cmp(&0)
+    }
+}
+
+impl<const BASE: u32, D: Digits> PartialOrd for PositionalNumber<BASE, D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const BASE: u32, D: Digits> Sum for PositionalNumber<BASE, D> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |a, b| a + b)
+    }
+}
+
+pub struct BinaryDigits;
+
+impl Digits for BinaryDigits {
+    const ALPHABET: &'static [char] = &['0', '1'];
+    const BALANCED: bool = false;
+}
+
+pub struct DecimalDigits;
+
+impl Digits for DecimalDigits {
+    const ALPHABET: &'static [char] = &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+    const BALANCED: bool = false;
+}
+
+pub struct HexadecimalDigits;
+
+impl Digits for HexadecimalDigits {
+    const ALPHABET: &'static [char] = &[
+        '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f',
+    ];
+    const BALANCED: bool = false;
+}
+
+pub struct BalancedTernaryDigits;
+
+impl Digits for BalancedTernaryDigits {
+    const ALPHABET: &'static [char] = &['-', '0', '+'];
+    const BALANCED: bool = true;
+}
+
+pub type Binary = PositionalNumber<2, BinaryDigits>;
+pub type Decimal = PositionalNumber<10, DecimalDigits>;
+pub type Hexadecimal = PositionalNumber<16, HexadecimalDigits>;
+pub type BalancedTernary = PositionalNumber<3, BalancedTernaryDigits>;
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+
+    use crate::{snafu::SnafuDigits, testing::XorShift};
+
+    use super::{
+        BalancedTernary, BalancedTernaryDigits, Binary, Decimal, DecimalDigits, Digits,
+        Hexadecimal, NumberOverflowError, ParseNumberError, PositionalNumber,
+    };
+
+    fn check_arithmetic<const BASE: u32, D: Digits>(rng: &mut XorShift) {
+        for _ in 0..10_000 {
+            let (a, b) = (rng.next_i128(), rng.next_i128());
+            let x = PositionalNumber::<BASE, D>::from(a);
+            let y = PositionalNumber::<BASE, D>::from(b);
+
+            assert_eq!(Ok(a), i128::try_from(&x));
+            assert_eq!(Ok(x.clone()), x.to_string().parse());
+
+            assert_eq!(
+                PositionalNumber::from(a + b),
+                x.clone() + y.clone(),
+                "{} + {}",
+                a,
+                b
+            );
+            assert_eq!(
+                PositionalNumber::from(a - b),
+                x.clone() - y.clone(),
+                "{} - {}",
+                a,
+                b
+            );
+            assert_eq!(
+                PositionalNumber::from(a * b),
+                x.clone() * y.clone(),
+                "{} * {}",
+                a,
+                b
+            );
+            assert_eq!(PositionalNumber::from(-a), -x.clone());
+            assert_eq!(a.cmp(&b), x.cmp(&y), "{} <=> {}", a, b);
+        }
+    }
+
+    fn check_arbitrary_precision<const BASE: u32, D: Digits>(rng: &mut XorShift) {
+        for _ in 0..1_000 {
+            let a: BigInt = (0..4).fold(BigInt::from(rng.next_i128()), |a, _| {
+                a * rng.next_i128() + rng.next_i128()
+            });
+            let b = BigInt::from(rng.next_i128()) * rng.next_i128();
+            let x = PositionalNumber::<BASE, D>::from(&a);
+            let y = PositionalNumber::<BASE, D>::from(&b);
+
+            assert_eq!(a, BigInt::from(&x));
+            assert_eq!(&a + &b, BigInt::from(&(x.clone() + y.clone())));
+            assert_eq!(&a - &b, BigInt::from(&(x.clone() - y.clone())));
+            assert_eq!(&a * &b, BigInt::from(&(x.clone() * y.clone())));
+            assert_eq!(a.cmp(&b), x.cmp(&y));
+        }
+
+        let max = PositionalNumber::<BASE, D>::from(i128::MAX);
+        let min = PositionalNumber::<BASE, D>::from(i128::MIN);
+        assert_eq!(Ok(i128::MAX), i128::try_from(&max));
+        assert_eq!(Ok(i128::MIN), i128::try_from(&min));
+        assert_eq!(
+            Err(NumberOverflowError),
+            i128::try_from(&(max + PositionalNumber::from(1)))
+        );
+        assert_eq!(
+            Err(NumberOverflowError),
+            i128::try_from(&(min - PositionalNumber::from(1)))
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_matches_integers() {
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+
+        check_arithmetic::<5, SnafuDigits>(&mut rng);
+        check_arithmetic::<10, DecimalDigits>(&mut rng);
+        check_arithmetic::<3, BalancedTernaryDigits>(&mut rng);
+    }
+
+    #[test]
+    fn test_arbitrary_precision() {
+        let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+
+        check_arbitrary_precision::<5, SnafuDigits>(&mut rng);
+        check_arbitrary_precision::<10, DecimalDigits>(&mut rng);
+    }
+
+    #[test]
+    fn test_standard_systems() {
+        let mut rng = XorShift(0x5851_f42d_4c95_7f2d);
+
+        for _ in 0..10_000 {
+            let a = rng.next_i128();
+
+            assert_eq!(a.to_string(), Decimal::from(a).to_string());
+            assert_eq!(
+                format!("{:b}", a.unsigned_abs()),
+                Binary::from(a.abs()).to_string()
+            );
+            assert_eq!(
+                format!("{:x}", a.unsigned_abs()),
+                Hexadecimal::from(a.abs()).to_string()
+            );
+            assert_eq!(Ok(Decimal::from(a)), a.to_string().parse());
+        }
+
+        assert_eq!(
+            "-340282366920938463463374607431768211456",
+            (Decimal::from(i128::MIN) * Decimal::from(2)).to_string()
+        );
+        assert_eq!(Ok(i128::MIN), i128::try_from(&Decimal::from(i128::MIN)));
+        assert_eq!("0", Decimal::from(0).to_string());
+        assert_eq!(
+            Err(ParseNumberError {
+                column: 2,
+                character: None
+            }),
+            "-".parse::<Decimal>()
+        );
+        assert_eq!(
+            Err(ParseNumberError {
+                column: 4,
+                character: Some('a')
+            }),
+            "-12a".parse::<Decimal>()
+        );
+    }
+
+    #[test]
+    fn test_balanced_ternary() {
+        let tests = [
+            (0, "0"),
+            (1, "+"),
+            (2, "+-"),
+            (8, "+0-"),
+            (-8, "-0+"),
+            (-13, "---"),
+            (1000, "++0+00+"),
+        ];
+
+        for (value, text) in tests {
+            assert_eq!(text, BalancedTernary::from(value).to_string());
+            assert_eq!(Ok(BalancedTernary::from(value)), text.parse());
+            assert_eq!(
+                BigInt::from(value),
+                BigInt::from(&text.parse::<BalancedTernary>().unwrap())
+            );
+        }
+    }
+}
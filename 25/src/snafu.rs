@@ -0,0 +1,48 @@
+use std::fmt::Display;
+
+use crate::positional::{Digits, ParseNumberError, PositionalNumber};
+
+pub struct SnafuDigits;
+
+impl Digits for SnafuDigits {
+    const ALPHABET: &'static [char] = &['=', '-', '0', '1', '2'];
+    const BALANCED: bool = true;
+}
+
+// Balanced base five.
+#[allow(clippy::upper_case_acronyms)]
+pub type SNAFU = PositionalNumber<5, SnafuDigits>;
+
+// Where in a file a SNAFU number couldn't be read, counting lines and columns from one.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseSNAFUError {
+    pub line: usize,
+    pub column: usize,
+    pub character: Option<char>,
+}
+
+impl Display for ParseSNAFUError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.character {
+            Some(character) => write!(
+                f,
+                "line {}, column {}: {:?} is not a SNAFU digit",
+                self.line, self.column, character
+            ),
+            None => write!(
+                f,
+                "line {}, column {}: expected a SNAFU digit",
+                self.line, self.column
+            ),
+        }
+    }
+}
+
+pub fn parse_snafu_line(line: usize, text: &str) -> Result<SNAFU, ParseSNAFUError> {
+    text.parse()
+        .map_err(|error: ParseNumberError| ParseSNAFUError {
+            line,
+            column: error.column,
+            character: error.character,
+        })
+}
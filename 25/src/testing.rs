@@ -0,0 +1,18 @@
+// A small deterministic generator for the property tests, so failures can be reproduced.
+pub struct XorShift(pub u64);
+
+impl XorShift {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // Mostly small numbers, so carries past the end and zero come up often. They stay below
+    // 2^63 either way, so the product of two always fits.
+    pub fn next_i128(&mut self) -> i128 {
+        let bits = 1 + self.next() % 63;
+        (self.next() >> (64 - bits)) as i128 * if self.next().is_multiple_of(2) { 1 } else { -1 }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::Crate;
+
+// How a crane sets down a block of crates it has taken off the top of a stack. The block is
+// given bottom first, as it was on the stack it came from.
+pub trait Crane {
+    // Where each crate of a block of `len` ends up: the crate set down `i`th from the bottom is
+    // the one that was `permutation(len)[i]`th from the bottom before.
+    fn permutation(&self, len: usize) -> Vec<usize>;
+
+    fn arrange(&self, block: Vec<Crate>) -> Vec<Crate> {
+        self.permutation(block.len())
+            .into_iter()
+            .map(|i| block[i].clone())
+            .collect()
+    }
+
+    // The block as it was before `arrange` set it down.
+    fn unarrange(&self, landed: Vec<Crate>) -> Vec<Crate> {
+        let mut block: Vec<(usize, Crate)> = self
+            .permutation(landed.len())
+            .into_iter()
+            .zip(landed)
+            .collect();
+        block.sort_by_key(|(i, _)| *i);
+
+        block.into_iter().map(|(_, c)| c).collect()
+    }
+}
+
+// Moves one crate at a time, so the block ends up upside down.
+pub struct CrateMover9000;
+
+// Moves the whole block at once, so it keeps its order.
+pub struct CrateMover9001;
+
+// Moves up to `capacity` crates at a time, each lift keeping its order.
+pub struct BatchCrane {
+    pub capacity: usize,
+}
+
+// Moves the whole block at once, but the top `flipped` crates turn over on the way.
+pub struct PartialReverseCrane {
+    pub flipped: usize,
+}
+
+impl<C: Crane + ?Sized> Crane for &C {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        (*self).permutation(len)
+    }
+}
+
+impl Crane for Box<dyn Crane> {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        self.as_ref().permutation(len)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseCraneError(String);
+
+// "9000", "9001", "batch=<capacity>" or "flip=<crates>".
+pub fn parse_crane(s: &str) -> Result<Box<dyn Crane>, ParseCraneError> {
+    let number = |value: &str| {
+        value
+            .parse::<usize>()
+            .map_err(|_| ParseCraneError(format!("Expected a number, got {}", value)))
+    };
+
+    match s.split_once('=') {
+        None if s == "9000" => Ok(Box::new(CrateMover9000)),
+        None if s == "9001" => Ok(Box::new(CrateMover9001)),
+        Some(("batch", capacity)) => Ok(Box::new(BatchCrane {
+            capacity: number(capacity)?,
+        })),
+        Some(("flip", flipped)) => Ok(Box::new(PartialReverseCrane {
+            flipped: number(flipped)?,
+        })),
+        _ => Err(ParseCraneError(format!("Unknown crane {}", s))),
+    }
+}
+
+impl Crane for CrateMover9000 {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        (0..len).rev().collect()
+    }
+}
+
+impl Crane for CrateMover9001 {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        (0..len).collect()
+    }
+}
+
+impl Crane for BatchCrane {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        (0..len)
+            .collect::<Vec<usize>>()
+            .rchunks(self.capacity.max(1))
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+impl Crane for PartialReverseCrane {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        let start = len.saturating_sub(self.flipped);
+        (0..start).chain((start..len).rev()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Crate;
+
+    use super::{
+        parse_crane, BatchCrane, Crane, CrateMover9000, CrateMover9001, ParseCraneError,
+        PartialReverseCrane,
+    };
+
+    fn arrange(crane: &dyn Crane, block: &str) -> String {
+        crane
+            .arrange(block.chars().map(Crate::from).collect())
+            .into_iter()
+            .map(|c| c.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_arrange() {
+        assert_eq!("EDCBA", arrange(&CrateMover9000, "ABCDE"));
+        assert_eq!("ABCDE", arrange(&CrateMover9001, "ABCDE"));
+        assert_eq!("EDCBA", arrange(&BatchCrane { capacity: 1 }, "ABCDE"));
+        assert_eq!("DEBCA", arrange(&BatchCrane { capacity: 2 }, "ABCDE"));
+        assert_eq!("ABCDE", arrange(&BatchCrane { capacity: 5 }, "ABCDE"));
+        assert_eq!(
+            "ABCED",
+            arrange(&PartialReverseCrane { flipped: 2 }, "ABCDE")
+        );
+        assert_eq!(
+            "EDCBA",
+            arrange(&PartialReverseCrane { flipped: 9 }, "ABCDE")
+        );
+    }
+
+    #[test]
+    fn test_permutation() {
+        assert_eq!(vec![2, 1, 0], CrateMover9000.permutation(3));
+        assert_eq!(vec![0, 1, 2], CrateMover9001.permutation(3));
+        assert_eq!(
+            vec![3, 4, 1, 2, 0],
+            BatchCrane { capacity: 2 }.permutation(5)
+        );
+        assert_eq!(
+            vec![0, 1, 4, 3, 2],
+            PartialReverseCrane { flipped: 3 }.permutation(5)
+        );
+        assert!(BatchCrane { capacity: 0 }.permutation(0).is_empty());
+    }
+
+    #[test]
+    fn test_unarrange() {
+        let cranes: Vec<Box<dyn Crane>> = vec![
+            Box::new(CrateMover9000),
+            Box::new(CrateMover9001),
+            Box::new(BatchCrane { capacity: 2 }),
+            Box::new(PartialReverseCrane { flipped: 3 }),
+        ];
+
+        for crane in cranes {
+            for block in ["", "A", "ABCDE", "ABCDEFG"] {
+                let landed = crane.arrange(block.chars().map(Crate::from).collect());
+                let unarranged: String = crane.unarrange(landed).into_iter().map(|c| c.0).collect();
+                assert_eq!(block, unarranged);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_crane() {
+        assert_eq!("DEBCA", arrange(&parse_crane("batch=2").unwrap(), "ABCDE"));
+        assert_eq!("ABCED", arrange(&parse_crane("flip=2").unwrap(), "ABCDE"));
+        assert_eq!("EDCBA", arrange(&parse_crane("9000").unwrap(), "ABCDE"));
+        assert_eq!(
+            Some(ParseCraneError("Expected a number, got x".to_string())),
+            parse_crane("batch=x").err()
+        );
+        assert_eq!(
+            Some(ParseCraneError("Unknown crane 9002".to_string())),
+            parse_crane("9002").err()
+        );
+    }
+}
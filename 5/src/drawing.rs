@@ -0,0 +1,253 @@
+use std::ops::Range;
+
+use crate::{Crate, Stack, StackID};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseDrawingError {
+    MissingIds,
+    InvalidId(String),
+    UnclosedCrate { row: usize, column: usize },
+    UnalignedCrate { row: usize, column: usize },
+}
+
+// The words of a line and the columns they span, counting in characters.
+fn split_words(line: &str) -> Vec<(Range<usize>, String)> {
+    let mut words = Vec::new();
+    let mut word: Option<(usize, String)> = None;
+
+    for (column, c) in line.chars().chain(std::iter::once(' ')).enumerate() {
+        match (c.is_whitespace(), word.as_mut()) {
+            (true, Some(_)) => {
+                let (start, text) = word.take().unwrap();
+                words.push((start..column, text));
+            }
+            (false, Some((_, text))) => text.push(c),
+            (false, None) => word = Some((column, c.to_string())),
+            (true, None) => {}
+        }
+    }
+
+    words
+}
+
+// Every `[label]` in a row of the drawing and the columns it spans. Labels may be any width
+// and the row may stop short of the last stack.
+pub fn parse_stack_line(
+    row: usize,
+    line: &str,
+) -> Result<Vec<(Range<usize>, Crate)>, ParseDrawingError> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut crates = Vec::new();
+    let mut column = 0;
+
+    while column < chars.len() {
+        if chars[column] != '[' {
+            column += 1;
+            continue;
+        }
+
+        let end = chars[column..]
+            .iter()
+            .position(|&c| c == ']')
+            .map(|length| column + length + 1)
+            .ok_or(ParseDrawingError::UnclosedCrate { row, column })?;
+
+        let label: String = chars[column + 1..end - 1].iter().collect();
+        crates.push((column..end, Crate(label)));
+        column = end;
+    }
+
+    Ok(crates)
+}
+
+fn parse_id_line(line: &str) -> Result<Vec<(Range<usize>, StackID)>, ParseDrawingError> {
+    split_words(line)
+        .into_iter()
+        .map(|(columns, id)| {
+            id.parse::<StackID>()
+                .map(|id| (columns, id))
+                .map_err(|_| ParseDrawingError::InvalidId(id))
+        })
+        .collect()
+}
+
+fn overlap(a: &Range<usize>, b: &Range<usize>) -> usize {
+    a.end.min(b.end).saturating_sub(a.start.max(b.start))
+}
+
+// Each crate belongs to the stack whose id it sits over the most.
+pub fn parse_stacks(input: &str) -> Result<Vec<Stack>, ParseDrawingError> {
+    let lines: Vec<(usize, &str)> = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+    let ((_, id_line), crate_lines) = lines.split_last().ok_or(ParseDrawingError::MissingIds)?;
+
+    let ids = parse_id_line(id_line)?;
+    if ids.is_empty() {
+        return Err(ParseDrawingError::MissingIds);
+    }
+
+    let mut stacks: Vec<Stack> = ids
+        .iter()
+        .map(|(_, id)| Stack {
+            id: *id,
+            stack: Vec::new(),
+        })
+        .collect();
+
+    for &(row, line) in crate_lines.iter().rev() {
+        for (columns, c) in parse_stack_line(row, line)? {
+            let (i, (id_columns, _)) = ids
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, (id_columns, _))| overlap(&columns, id_columns))
+                .unwrap();
+
+            if overlap(&columns, id_columns) == 0 {
+                return Err(ParseDrawingError::UnalignedCrate {
+                    row,
+                    column: columns.start,
+                });
+            }
+
+            stacks[i].stack.push(c);
+        }
+    }
+
+    Ok(stacks)
+}
+
+// Draws the stacks the way the puzzle does, every column as wide as the widest crate or id.
+pub fn render_stacks(stacks: &[Stack]) -> String {
+    let width = stacks
+        .iter()
+        .flat_map(|stack| stack.stack.iter().map(|c| c.0.chars().count() + 2))
+        .chain(stacks.iter().map(|stack| stack.id.to_string().len()))
+        .max()
+        .unwrap_or(0)
+        .max(3);
+    let height = stacks
+        .iter()
+        .map(|stack| stack.stack.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines: Vec<String> = (0..height)
+        .rev()
+        .map(|level| {
+            stacks
+                .iter()
+                .map(|stack| match stack.stack.get(level) {
+                    Some(c) => format!("{:<width$}", format!("[{}]", c.0), width = width),
+                    None => " ".repeat(width),
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect();
+
+    lines.push(
+        stacks
+            .iter()
+            .map(|stack| {
+                let id = stack.id.to_string();
+                let left = (width - id.len()) / 2;
+                format!("{}{:<width$}", " ".repeat(left), id, width = width - left)
+            })
+            .collect::<Vec<String>>()
+            .join(" "),
+    );
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Crate, Stack};
+
+    use super::{parse_stack_line, parse_stacks, render_stacks, ParseDrawingError};
+
+    const DRAWING: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+
+    fn stack(id: u8, labels: &[&str]) -> Stack {
+        Stack {
+            id,
+            stack: labels
+                .iter()
+                .map(|label| Crate(label.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_stack_line() {
+        assert_eq!(
+            Ok(vec![(4..7, Crate::from('D'))]),
+            parse_stack_line(0, "    [D]    ")
+        );
+        assert_eq!(
+            Ok(vec![
+                (0..4, Crate("AB".to_string())),
+                (9..12, Crate::from('C'))
+            ]),
+            parse_stack_line(0, "[AB]     [C]")
+        );
+        assert_eq!(
+            Err(ParseDrawingError::UnclosedCrate { row: 2, column: 4 }),
+            parse_stack_line(2, "[A] [B")
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let stacks = parse_stacks(DRAWING).unwrap();
+
+        assert_eq!(
+            vec![
+                stack(1, &["Z", "N"]),
+                stack(2, &["M", "C", "D"]),
+                stack(3, &["P"])
+            ],
+            stacks
+        );
+        assert_eq!(DRAWING, render_stacks(&stacks));
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        let drawing = "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3";
+
+        assert_eq!(parse_stacks(DRAWING), parse_stacks(drawing));
+    }
+
+    #[test]
+    fn test_wide_labels_and_ids() {
+        let stacks = vec![stack(9, &["A", "BIG"]), stack(10, &[]), stack(11, &["C"])];
+        let drawing = render_stacks(&stacks);
+
+        assert_eq!(
+            "[BIG]            \n[A]         [C]  \n  9    10    11  ",
+            drawing
+        );
+        assert_eq!(Ok(stacks), parse_stacks(&drawing));
+        assert_eq!(
+            Ok(vec![stack(10, &["XY"]), stack(11, &["Z"])]),
+            parse_stacks("[XY] [Z]\n 10   11")
+        );
+    }
+
+    #[test]
+    fn test_bad_drawings() {
+        assert_eq!(Err(ParseDrawingError::MissingIds), parse_stacks(""));
+        assert_eq!(
+            Err(ParseDrawingError::InvalidId("x".to_string())),
+            parse_stacks("[A]\n x ")
+        );
+        assert_eq!(
+            Err(ParseDrawingError::UnalignedCrate { row: 0, column: 8 }),
+            parse_stacks("[A]     [B]\n 1   2 ")
+        );
+    }
+}
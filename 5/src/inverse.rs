@@ -0,0 +1,194 @@
+use crate::{
+    crane::Crane,
+    simulator::{CraneError, ProcedureError, Simulator},
+    Instruction, Stack, StackID,
+};
+
+// Why a procedure doesn't take an initial drawing to a final one.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InconsistencyError {
+    Failed(ProcedureError),
+    // The first stack that ended up different from the final drawing.
+    Diverged(StackID),
+    DifferentStacks,
+}
+
+fn find_stack(stacks: &[Stack], id: StackID) -> Result<usize, CraneError> {
+    stacks
+        .iter()
+        .position(|stack| stack.id == id)
+        .ok_or(CraneError::UnknownStack(id))
+}
+
+// Takes the block an instruction set down back to the stack it came from.
+fn unstep(
+    crane: &impl Crane,
+    stacks: &mut [Stack],
+    instruction: &Instruction,
+) -> Result<(), CraneError> {
+    let from = find_stack(stacks, instruction.from)?;
+    let to = find_stack(stacks, instruction.to)?;
+
+    let available = stacks[to].stack.len();
+    if instruction.amount as usize > available {
+        return Err(CraneError::NotEnoughCrates {
+            stack: instruction.to,
+            wanted: instruction.amount,
+            available,
+        });
+    }
+
+    let landed = stacks[to]
+        .stack
+        .split_off(available - instruction.amount as usize);
+    stacks[from].stack.extend(crane.unarrange(landed));
+
+    Ok(())
+}
+
+// Works out the drawing a procedure must have started from to end with `finished`. Errors
+// count instructions from the start of the procedure, like the forward simulator.
+pub fn reconstruct(
+    crane: &impl Crane,
+    procedure: &[Instruction],
+    finished: &[Stack],
+) -> Result<Vec<Stack>, ProcedureError> {
+    let mut stacks = finished.to_vec();
+
+    for (step, instruction) in procedure.iter().enumerate().rev() {
+        unstep(crane, &mut stacks, instruction).map_err(|error| ProcedureError { step, error })?;
+    }
+
+    Ok(stacks)
+}
+
+// Checks that running the procedure forwards from `initial` ends with `finished`.
+pub fn check_consistency(
+    crane: &impl Crane,
+    initial: &[Stack],
+    procedure: &[Instruction],
+    finished: &[Stack],
+) -> Result<(), InconsistencyError> {
+    let mut simulator = Simulator::new(crane, initial.to_vec());
+    simulator
+        .run(procedure)
+        .map_err(InconsistencyError::Failed)?;
+
+    let ids = |stacks: &[Stack]| stacks.iter().map(|stack| stack.id).collect::<Vec<_>>();
+    if ids(simulator.get_stacks()) != ids(finished) {
+        return Err(InconsistencyError::DifferentStacks);
+    }
+
+    match simulator
+        .get_stacks()
+        .iter()
+        .zip(finished)
+        .find(|(actual, expected)| actual != expected)
+    {
+        Some((stack, _)) => Err(InconsistencyError::Diverged(stack.id)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        crane::{BatchCrane, Crane, CrateMover9000, CrateMover9001, PartialReverseCrane},
+        drawing::parse_stacks,
+        parse_input,
+        simulator::{CraneError, ProcedureError, Simulator},
+    };
+
+    use super::{check_consistency, reconstruct, InconsistencyError};
+
+    const INPUT: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2";
+
+    #[test]
+    fn test_reconstruct() {
+        let (stacks, procedure) = parse_input(INPUT).unwrap();
+        let cranes: Vec<Box<dyn Crane>> = vec![
+            Box::new(CrateMover9000),
+            Box::new(CrateMover9001),
+            Box::new(BatchCrane { capacity: 2 }),
+            Box::new(PartialReverseCrane { flipped: 1 }),
+        ];
+
+        for crane in cranes {
+            let mut simulator = Simulator::new(&crane, stacks.clone());
+            simulator.run(&procedure).unwrap();
+            let finished = simulator.get_stacks().to_vec();
+
+            assert_eq!(
+                Ok(stacks.clone()),
+                reconstruct(&crane, &procedure, &finished)
+            );
+            assert_eq!(
+                Ok(()),
+                check_consistency(&crane, &stacks, &procedure, &finished)
+            );
+        }
+    }
+
+    #[test]
+    fn test_puzzle_final_drawings() {
+        let (stacks, procedure) = parse_input(INPUT).unwrap();
+        let after_9000 =
+            parse_stacks("        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3 ")
+                .unwrap();
+        let after_9001 =
+            parse_stacks("        [D]\n        [N]\n        [Z]\n[M] [C] [P]\n 1   2   3 ")
+                .unwrap();
+
+        assert_eq!(
+            Ok(stacks.clone()),
+            reconstruct(&CrateMover9000, &procedure, &after_9000)
+        );
+        assert_eq!(
+            Ok(stacks.clone()),
+            reconstruct(&CrateMover9001, &procedure, &after_9001)
+        );
+
+        assert_eq!(
+            Err(InconsistencyError::Diverged(1)),
+            check_consistency(&CrateMover9000, &stacks, &procedure, &after_9001)
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_drawings() {
+        let (stacks, procedure) = parse_input(INPUT).unwrap();
+        let too_few = parse_stacks("[C] [M] [P]\n 1   2   3 ").unwrap();
+        let fewer_stacks = parse_stacks("[C] [M]\n 1   2 ").unwrap();
+
+        assert_eq!(
+            Err(ProcedureError {
+                step: 1,
+                error: CraneError::NotEnoughCrates {
+                    stack: 3,
+                    wanted: 3,
+                    available: 1
+                }
+            }),
+            reconstruct(&CrateMover9000, &procedure, &too_few)
+        );
+        assert_eq!(
+            Err(ProcedureError {
+                step: 3,
+                error: CraneError::NotEnoughCrates {
+                    stack: 2,
+                    wanted: 1,
+                    available: 0
+                }
+            }),
+            reconstruct(
+                &CrateMover9000,
+                &procedure,
+                &parse_stacks("[C]\n 1   2   3").unwrap()
+            )
+        );
+        assert_eq!(
+            Err(InconsistencyError::DifferentStacks),
+            check_consistency(&CrateMover9000, &stacks, &procedure, &fewer_stacks)
+        );
+    }
+}
@@ -1,17 +1,36 @@
-use std::fs;
-
-#[derive(Debug, PartialEq, Copy, Clone)]
-struct Crate(char);
+mod crane;
+mod drawing;
+mod inverse;
+mod simulator;
+
+use crane::{parse_crane, Crane, CrateMover9000, CrateMover9001};
+use drawing::{parse_stacks, render_stacks, ParseDrawingError};
+use inverse::{check_consistency, reconstruct};
+use simulator::Simulator;
+use std::{
+    env, fs,
+    io::{self, BufRead},
+    process,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Crate(String);
+
+impl From<char> for Crate {
+    fn from(label: char) -> Self {
+        Crate(label.to_string())
+    }
+}
 
 type StackID = u8;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 struct Stack {
     id: StackID,
     stack: Vec<Crate>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 struct Instruction {
     amount: u32,
     from: StackID,
@@ -24,167 +43,240 @@ fn load_input() -> String {
     fs::read_to_string("input.txt").expect("Should have been able to read the file")
 }
 
-fn parse_crate(input: &str) -> Option<Crate> {
-    assert_eq!(input.len(), 3);
-
-    if input == "   " {
-        return None;
-    }
-
-    Some(Crate(input.chars().nth(1).unwrap()))
+// The procedure line, counting from one, that isn't a `move N from A to B` instruction.
+#[derive(Debug, PartialEq, Eq)]
+struct ParseInstructionError {
+    line: usize,
+    instruction: String,
 }
 
-fn parse_stack_line(input: &String) -> Vec<Option<Crate>> {
-    let mut stack_line = Vec::new();
-    let mut chars = input.chars();
-
-    loop {
-        let maybe_crate_str: String = (0..).take(3).map(|_| chars.next().unwrap()).collect();
-
-        let maybe_crate = parse_crate(&maybe_crate_str);
-        stack_line.push(maybe_crate);
+#[derive(Debug, PartialEq, Eq)]
+enum ParseInputError {
+    MissingProcedure,
+    Drawing(ParseDrawingError),
+    Instruction(ParseInstructionError),
+}
 
-        let space = chars.next();
+impl From<ParseDrawingError> for ParseInputError {
+    fn from(error: ParseDrawingError) -> Self {
+        ParseInputError::Drawing(error)
+    }
+}
 
-        if space.is_none() {
-            break;
-        }
+impl From<ParseInstructionError> for ParseInputError {
+    fn from(error: ParseInstructionError) -> Self {
+        ParseInputError::Instruction(error)
     }
+}
 
-    stack_line
+fn parse_instruction(line: usize, input: &str) -> Result<Instruction, ParseInstructionError> {
+    let error = || ParseInstructionError {
+        line,
+        instruction: input.to_string(),
+    };
+
+    match input.split(' ').collect::<Vec<&str>>()[..] {
+        ["move", amount, "from", from, "to", to] => Ok(Instruction {
+            amount: amount.parse::<u32>().map_err(|_| error())?,
+            from: from.parse::<StackID>().map_err(|_| error())?,
+            to: to.parse::<StackID>().map_err(|_| error())?,
+        }),
+        _ => Err(error()),
+    }
 }
 
-fn parse_id_line(input: &str) -> Vec<StackID> {
-    String::from(input)
-        .trim()
-        .split("   ")
-        .map(|id| id.parse::<StackID>().unwrap())
+// Blank lines, such as one at the end of the file, are skipped.
+fn parse_procedure(input: &str) -> Result<Procedure, ParseInstructionError> {
+    input
+        .split('\n')
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| parse_instruction(i + 1, line))
         .collect()
 }
 
-fn create_stacks(rows: &[&[Option<Crate>]], ids: &[StackID]) -> Vec<Stack> {
-    let mut stacks: Vec<Vec<Crate>> = Vec::new();
-
-    for _ in ids {
-        stacks.push(Vec::new())
-    }
-
-    for row in rows.iter().rev() {
-        for (i, maybe_crate) in row.iter().enumerate() {
-            match maybe_crate {
-                Some(c) => stacks[i].push(c.clone()),
-                None => continue,
-            }
-        }
-    }
+fn parse_input(input: &str) -> Result<(Vec<Stack>, Procedure), ParseInputError> {
+    let (stacks_str, procedure_str) = input
+        .split_once("\n\n")
+        .ok_or(ParseInputError::MissingProcedure)?;
+    Ok((parse_stacks(stacks_str)?, parse_procedure(procedure_str)?))
+}
 
-    ids.into_iter()
-        .zip(stacks.into_iter())
-        .map(|(id, stack)| Stack {
-            id: id.clone(),
-            stack,
-        })
+// An empty stack shows as a space.
+fn get_top_crates(stacks: &[Stack]) -> String {
+    stacks
+        .iter()
+        .map(|stack| stack.stack.last().map_or(" ", |c| c.0.as_str()))
         .collect()
 }
 
-fn parse_stacks(input: &str) -> Vec<Stack> {
-    let lines: Vec<String> = input.split('\n').map(String::from).collect();
-    let last_index = lines.len() - 1;
-    let stacks_lines = &lines[..last_index];
-    let id_line = &lines[last_index];
+fn get_top_crates_with(crane: impl Crane, stacks: &[Stack], procedure: &[Instruction]) -> String {
+    let mut simulator = Simulator::new(crane, stacks.to_vec());
 
-    let stack_rows: Vec<Vec<Option<Crate>>> = stacks_lines.iter().map(parse_stack_line).collect();
-    let ids: Vec<StackID> = parse_id_line(id_line);
+    simulator.run(procedure).unwrap_or_else(|error| {
+        eprintln!(
+            "Instruction {} can't be carried out: {:?}",
+            error.step + 1,
+            error.error
+        );
+        process::exit(1);
+    });
 
-    let slice_2d: Vec<&[Option<Crate>]> = stack_rows.iter().map(Vec::as_slice).collect();
-    create_stacks(&slice_2d, &ids)
+    get_top_crates(simulator.get_stacks())
 }
 
-fn parse_instruction(input: &str) -> Instruction {
-    let mut split = input.split(" ");
-    Instruction {
-        amount: split.nth(1).unwrap().parse::<u32>().unwrap(),
-        from: split.nth(1).unwrap().parse::<StackID>().unwrap(),
-        to: split.nth(1).unwrap().parse::<StackID>().unwrap(),
-    }
+fn load_output_format() -> Option<String> {
+    env::args().nth(2)
 }
 
-fn parse_procedure(input: &str) -> Procedure {
-    input.split('\n').map(parse_instruction).collect()
-}
+// Steps through the procedure a command at a time: next, undo, redo or quit.
+fn interact(crane: Box<dyn Crane>, stacks: Vec<Stack>, procedure: &[Instruction]) {
+    let mut simulator = Simulator::new(crane, stacks);
 
-fn parse_input(input: &str) -> (Vec<Stack>, Procedure) {
-    let (stacks_str, procedure_str) = input.split_once("\n\n").unwrap();
-    (parse_stacks(&stacks_str), parse_procedure(&procedure_str))
-}
+    for line in io::stdin().lock().lines() {
+        let command = line.expect("Should be able to read the command");
 
-fn run_instruction<'a>(
-    stacks: &'a mut Vec<Stack>,
-    instruction: &Instruction,
-) -> &'a mut Vec<Stack> {
-    for _ in 0..instruction.amount {
-        let from = &mut stacks[(instruction.from - 1) as usize];
-        let from_crate = from.stack.pop().unwrap();
-        let to = &mut stacks[(instruction.to - 1) as usize];
+        let done = match command.trim() {
+            "next" | "n" => match procedure.get(simulator.get_moves_done()) {
+                Some(instruction) => simulator.step(instruction).map(|_| instruction.clone()),
+                None => {
+                    println!("The procedure is finished");
+                    continue;
+                }
+            },
+            "undo" | "u" => match simulator.undo() {
+                Some(instruction) => Ok(instruction),
+                None => {
+                    println!("Nothing to undo");
+                    continue;
+                }
+            },
+            "redo" | "r" => match simulator.redo() {
+                Some(instruction) => Ok(instruction),
+                None => {
+                    println!("Nothing to redo");
+                    continue;
+                }
+            },
+            "quit" | "q" => break,
+            _ => {
+                println!("Expected next, undo, redo or quit");
+                continue;
+            }
+        };
 
-        to.stack.push(from_crate);
+        match done {
+            Ok(instruction) => println!(
+                "{}: move {} from {} to {}, top crates {}",
+                simulator.get_moves_done(),
+                instruction.amount,
+                instruction.from,
+                instruction.to,
+                get_top_crates(simulator.get_stacks())
+            ),
+            Err(error) => println!("{:?}", error),
+        }
     }
-
-    stacks
 }
 
-fn run_instruction_9001<'a>(
-    stacks: &'a mut Vec<Stack>,
-    instruction: &Instruction,
-) -> &'a mut Vec<Stack> {
-    let mut queue = vec![];
-    for _ in 0..instruction.amount {
-        let from = &mut stacks[(instruction.from - 1) as usize];
-        let from_crate = from.stack.pop().unwrap();
-        queue.push(from_crate);
-    }
+// Draws the stacks before the procedure and again after every instruction.
+fn render(crane: Box<dyn Crane>, stacks: Vec<Stack>, procedure: &[Instruction]) {
+    let mut simulator = Simulator::new(crane, stacks);
+    println!("{}", render_stacks(simulator.get_stacks()));
 
-    let to = &mut stacks[(instruction.to - 1) as usize];
+    for instruction in procedure {
+        if let Err(error) = simulator.step(instruction) {
+            eprintln!("{:?}", error);
+            process::exit(1);
+        }
 
-    for _ in 0..instruction.amount {
-        to.stack.push(queue.pop().unwrap())
+        println!(
+            "\nmove {} from {} to {}\n{}",
+            instruction.amount,
+            instruction.from,
+            instruction.to,
+            render_stacks(simulator.get_stacks())
+        );
     }
-
-    stacks
 }
 
-fn run<'a>(
-    stacks: &'a mut Vec<Stack>,
+// Works back from a drawing of the finished stacks to the drawing they started as, and checks
+// it against the one in the input.
+fn reconstruct_from(
+    crane: Box<dyn Crane>,
+    stacks: &[Stack],
     procedure: &[Instruction],
-    processor: &dyn Fn(&'a mut Vec<Stack>, &Instruction) -> &'a mut Vec<Stack>,
-) -> &'a mut Vec<Stack> {
-    procedure.to_vec().iter().fold(stacks, processor)
-}
-
-fn get_top_crates(stacks: &[Stack]) -> String {
-    stacks
-        .iter()
-        .map(|stack| stack.stack.last().unwrap().0)
-        .collect()
+    path: &str,
+) {
+    let drawing = fs::read_to_string(path).expect("Should have been able to read the file");
+    let finished = parse_stacks(&drawing).unwrap_or_else(|error| {
+        eprintln!("Problem parsing the drawing: {:?}", error);
+        process::exit(1);
+    });
+
+    let initial = reconstruct(&crane, procedure, &finished).unwrap_or_else(|error| {
+        eprintln!(
+            "Instruction {} can't be undone: {:?}",
+            error.step + 1,
+            error.error
+        );
+        process::exit(1);
+    });
+    println!("{}", render_stacks(&initial));
+
+    match check_consistency(&crane, stacks, procedure, &finished) {
+        Ok(()) => println!("\nThe input's drawing ends this way"),
+        Err(error) => println!("\nThe input's drawing doesn't end this way: {:?}", error),
+    }
 }
 
 fn main() {
     let input = load_input();
-    let (mut stacks, procedure) = parse_input(&input);
-    let modified_stacks = run(&mut stacks, &procedure, &run_instruction);
-    let top_crates = get_top_crates(&modified_stacks);
-    println!("{}", top_crates);
+    let (stacks, procedure) = parse_input(&input).unwrap_or_else(|error| {
+        eprintln!("Problem parsing the input: {:?}", error);
+        process::exit(1);
+    });
+
+    if let Some(crane) = env::args().nth(1) {
+        let crane = parse_crane(&crane).unwrap_or_else(|error| {
+            eprintln!("{:?}", error);
+            process::exit(1);
+        });
+
+        match load_output_format().as_deref() {
+            None => println!("{}", get_top_crates_with(crane, &stacks, &procedure)),
+            Some("--interactive") => interact(crane, stacks, &procedure),
+            Some("--render") => render(crane, stacks, &procedure),
+            Some("--reconstruct") => {
+                let path = env::args()
+                    .nth(3)
+                    .expect("Expected a drawing of the final stacks");
+                reconstruct_from(crane, &stacks, &procedure, &path)
+            }
+            Some(format) => panic!("Unknown output format {}", format),
+        }
+
+        return;
+    }
+
+    println!(
+        "{}",
+        get_top_crates_with(CrateMover9000, &stacks, &procedure)
+    );
 
     // Part 2
-    let (mut stacks_2, _) = parse_input(&input);
-    let modified_stacks_2 = run(&mut stacks_2, &procedure, &run_instruction_9001);
-    let top_crates_2 = get_top_crates(&modified_stacks_2);
-    println!("{}", top_crates_2);
+    println!(
+        "{}",
+        get_top_crates_with(CrateMover9001, &stacks, &procedure)
+    );
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{parse_input, parse_stack_line, run, run_instruction, Crate, Instruction, Stack};
+    use crate::{
+        crane::CrateMover9000, drawing::ParseDrawingError, parse_input, simulator::Simulator,
+        Crate, Instruction, ParseInputError, ParseInstructionError, Stack,
+    };
 
     #[test]
     fn test_parse_input() {
@@ -192,15 +284,15 @@ mod tests {
         let expected_stacks = vec![
             Stack {
                 id: 1,
-                stack: vec![Crate('Z'), Crate('N')],
+                stack: vec![Crate::from('Z'), Crate::from('N')],
             },
             Stack {
                 id: 2,
-                stack: vec![Crate('M'), Crate('C'), Crate('D')],
+                stack: vec![Crate::from('M'), Crate::from('C'), Crate::from('D')],
             },
             Stack {
                 id: 3,
-                stack: vec![Crate('P')],
+                stack: vec![Crate::from('P')],
             },
         ];
 
@@ -227,33 +319,59 @@ mod tests {
             },
         ];
 
-        let (stacks, procedure) = parse_input(&input);
+        let (stacks, procedure) = parse_input(input).unwrap();
         assert_eq!(stacks, expected_stacks);
         assert_eq!(expected_procedure, procedure);
     }
 
     #[test]
-    fn test_parse_stack_line() {
-        let input = String::from("    [D]    ");
-        let expected = vec![None, Some(Crate('D')), None];
-        let actual = parse_stack_line(&input);
-        assert_eq!(expected, actual);
+    fn test_parse_input_errors() {
+        let drawing = "[Z] [M] [P]\n 1   2   3 ";
+
+        assert_eq!(
+            Err(ParseInputError::MissingProcedure),
+            parse_input(drawing).map(|_| ())
+        );
+        assert_eq!(
+            Err(ParseInputError::Drawing(ParseDrawingError::MissingIds)),
+            parse_input("\n\nmove 1 from 2 to 1").map(|_| ())
+        );
+
+        let bad_lines = [
+            "move 1 from 2",
+            "move one from 2 to 1",
+            "move 1 from 2 to 300",
+            "take 1 from 2 to 1",
+            "move 1 from 2 to 1 now",
+        ];
+        for line in bad_lines {
+            assert_eq!(
+                Err(ParseInputError::Instruction(ParseInstructionError {
+                    line: 2,
+                    instruction: line.to_string()
+                })),
+                parse_input(&format!("{}\n\nmove 1 from 2 to 1\n{}\n", drawing, line)).map(|_| ())
+            );
+        }
+
+        let (_, procedure) = parse_input(&format!("{}\n\nmove 1 from 2 to 1\n", drawing)).unwrap();
+        assert_eq!(1, procedure.len());
     }
 
     #[test]
     fn test_run_instruction() {
-        let mut stacks = vec![
+        let stacks = vec![
             Stack {
                 id: 1,
-                stack: vec![Crate('Z'), Crate('N')],
+                stack: vec![Crate::from('Z'), Crate::from('N')],
             },
             Stack {
                 id: 2,
-                stack: vec![Crate('M'), Crate('C'), Crate('D')],
+                stack: vec![Crate::from('M'), Crate::from('C'), Crate::from('D')],
             },
             Stack {
                 id: 3,
-                stack: vec![Crate('P')],
+                stack: vec![Crate::from('P')],
             },
         ];
 
@@ -263,23 +381,24 @@ mod tests {
             to: 1,
         };
 
-        let mut expected = vec![
+        let expected = vec![
             Stack {
                 id: 1,
-                stack: vec![Crate('Z'), Crate('N'), Crate('D')],
+                stack: vec![Crate::from('Z'), Crate::from('N'), Crate::from('D')],
             },
             Stack {
                 id: 2,
-                stack: vec![Crate('M'), Crate('C')],
+                stack: vec![Crate::from('M'), Crate::from('C')],
             },
             Stack {
                 id: 3,
-                stack: vec![Crate('P')],
+                stack: vec![Crate::from('P')],
             },
         ];
 
-        run_instruction(&mut stacks, &instruction);
-        assert_eq!(expected, stacks);
+        let mut simulator = Simulator::new(CrateMover9000, stacks);
+        simulator.step(&instruction).unwrap();
+        assert_eq!(expected, simulator.get_stacks());
 
         let instruction_2 = Instruction {
             amount: 3,
@@ -293,32 +412,37 @@ mod tests {
             },
             Stack {
                 id: 2,
-                stack: vec![Crate('M'), Crate('C')],
+                stack: vec![Crate::from('M'), Crate::from('C')],
             },
             Stack {
                 id: 3,
-                stack: vec![Crate('P'), Crate('D'), Crate('N'), Crate('Z')],
+                stack: vec![
+                    Crate::from('P'),
+                    Crate::from('D'),
+                    Crate::from('N'),
+                    Crate::from('Z'),
+                ],
             },
         ];
 
-        run_instruction(&mut expected, &instruction_2);
-        assert_eq!(expected_2, expected);
+        simulator.step(&instruction_2).unwrap();
+        assert_eq!(expected_2, simulator.get_stacks());
     }
 
     #[test]
     fn test_run() {
-        let mut input_stacks = vec![
+        let input_stacks = vec![
             Stack {
                 id: 1,
-                stack: vec![Crate('Z'), Crate('N')],
+                stack: vec![Crate::from('Z'), Crate::from('N')],
             },
             Stack {
                 id: 2,
-                stack: vec![Crate('M'), Crate('C'), Crate('D')],
+                stack: vec![Crate::from('M'), Crate::from('C'), Crate::from('D')],
             },
             Stack {
                 id: 3,
-                stack: vec![Crate('P')],
+                stack: vec![Crate::from('P')],
             },
         ];
 
@@ -348,19 +472,25 @@ mod tests {
         let expected_stacks = vec![
             Stack {
                 id: 1,
-                stack: vec![Crate('C')],
+                stack: vec![Crate::from('C')],
             },
             Stack {
                 id: 2,
-                stack: vec![Crate('M')],
+                stack: vec![Crate::from('M')],
             },
             Stack {
                 id: 3,
-                stack: vec![Crate('P'), Crate('D'), Crate('N'), Crate('Z')],
+                stack: vec![
+                    Crate::from('P'),
+                    Crate::from('D'),
+                    Crate::from('N'),
+                    Crate::from('Z'),
+                ],
             },
         ];
 
-        let _actual_stacks = run(&mut input_stacks, &input_procedure, &run_instruction);
-        assert_eq!(expected_stacks, input_stacks);
+        let mut simulator = Simulator::new(CrateMover9000, input_stacks);
+        simulator.run(&input_procedure).unwrap();
+        assert_eq!(expected_stacks, simulator.get_stacks());
     }
 }
@@ -0,0 +1,210 @@
+use crate::{crane::Crane, Crate, Instruction, Stack, StackID};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CraneError {
+    UnknownStack(StackID),
+    NotEnoughCrates {
+        stack: StackID,
+        wanted: u32,
+        available: usize,
+    },
+}
+
+// Which instruction of a procedure couldn't be carried out, counting from zero.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ProcedureError {
+    pub step: usize,
+    pub error: CraneError,
+}
+
+// A block of crates that was moved, as it was before the move, so the move can be undone.
+struct Move {
+    instruction: Instruction,
+    block: Vec<Crate>,
+}
+
+pub struct Simulator<C: Crane> {
+    crane: C,
+    stacks: Vec<Stack>,
+    done: Vec<Move>,
+    undone: Vec<Instruction>,
+}
+
+impl<C: Crane> Simulator<C> {
+    pub fn new(crane: C, stacks: Vec<Stack>) -> Simulator<C> {
+        Simulator {
+            crane,
+            stacks,
+            done: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn get_stacks(&self) -> &[Stack] {
+        &self.stacks
+    }
+
+    pub fn get_moves_done(&self) -> usize {
+        self.done.len()
+    }
+
+    fn find_stack(&self, id: StackID) -> Result<usize, CraneError> {
+        self.stacks
+            .iter()
+            .position(|stack| stack.id == id)
+            .ok_or(CraneError::UnknownStack(id))
+    }
+
+    fn apply(&mut self, instruction: &Instruction) -> Result<(), CraneError> {
+        let from = self.find_stack(instruction.from)?;
+        let to = self.find_stack(instruction.to)?;
+
+        let available = self.stacks[from].stack.len();
+        if instruction.amount as usize > available {
+            return Err(CraneError::NotEnoughCrates {
+                stack: instruction.from,
+                wanted: instruction.amount,
+                available,
+            });
+        }
+
+        let block = self.stacks[from]
+            .stack
+            .split_off(available - instruction.amount as usize);
+        self.stacks[to]
+            .stack
+            .extend(self.crane.arrange(block.clone()));
+
+        self.done.push(Move {
+            instruction: instruction.clone(),
+            block,
+        });
+
+        Ok(())
+    }
+
+    // Carries out an instruction, leaving the stacks as they were if it can't be done.
+    pub fn step(&mut self, instruction: &Instruction) -> Result<(), CraneError> {
+        self.apply(instruction)?;
+        self.undone.clear();
+
+        Ok(())
+    }
+
+    pub fn run(&mut self, procedure: &[Instruction]) -> Result<(), ProcedureError> {
+        for (step, instruction) in procedure.iter().enumerate() {
+            self.step(instruction)
+                .map_err(|error| ProcedureError { step, error })?;
+        }
+
+        Ok(())
+    }
+
+    // Puts the last move back, returning its instruction.
+    pub fn undo(&mut self) -> Option<Instruction> {
+        let Move { instruction, block } = self.done.pop()?;
+
+        let from = self.find_stack(instruction.from).unwrap();
+        let to = self.find_stack(instruction.to).unwrap();
+
+        let landed = self.stacks[to].stack.len() - block.len();
+        self.stacks[to].stack.truncate(landed);
+        self.stacks[from].stack.extend(block);
+
+        self.undone.push(instruction.clone());
+
+        Some(instruction)
+    }
+
+    // Carries out the last undone move again, returning its instruction.
+    pub fn redo(&mut self) -> Option<Instruction> {
+        let instruction = self.undone.pop()?;
+
+        // An undone instruction always fits the stacks it was undone from.
+        self.apply(&instruction).unwrap();
+
+        Some(instruction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        crane::{CrateMover9000, CrateMover9001},
+        get_top_crates, parse_input,
+    };
+
+    use super::{CraneError, ProcedureError, Simulator};
+
+    const INPUT: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2";
+
+    #[test]
+    fn test_cranes() {
+        let (stacks, procedure) = parse_input(INPUT).unwrap();
+
+        let mut simulator = Simulator::new(CrateMover9000, stacks.clone());
+        simulator.run(&procedure).unwrap();
+        assert_eq!("CMZ", get_top_crates(simulator.get_stacks()));
+
+        let mut simulator = Simulator::new(CrateMover9001, stacks);
+        simulator.run(&procedure).unwrap();
+        assert_eq!("MCD", get_top_crates(simulator.get_stacks()));
+    }
+
+    #[test]
+    fn test_invalid_instructions() {
+        let (stacks, mut procedure) = parse_input(INPUT).unwrap();
+        let mut simulator = Simulator::new(CrateMover9000, stacks.clone());
+
+        procedure[1].amount = 4;
+        assert_eq!(
+            Err(ProcedureError {
+                step: 1,
+                error: CraneError::NotEnoughCrates {
+                    stack: 1,
+                    wanted: 4,
+                    available: 3
+                }
+            }),
+            simulator.run(&procedure)
+        );
+
+        procedure[1].to = 4;
+        assert_eq!(
+            Err(CraneError::UnknownStack(4)),
+            simulator.step(&procedure[1])
+        );
+
+        assert_eq!(Some(procedure[0].clone()), simulator.undo());
+        assert_eq!(stacks, simulator.get_stacks());
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let (stacks, procedure) = parse_input(INPUT).unwrap();
+        let mut simulator = Simulator::new(CrateMover9001, stacks.clone());
+        simulator.run(&procedure).unwrap();
+        let finished = simulator.get_stacks().to_vec();
+
+        for instruction in procedure.iter().rev() {
+            assert_eq!(Some(instruction.clone()), simulator.undo());
+        }
+        assert_eq!(None, simulator.undo());
+        assert_eq!(stacks, simulator.get_stacks());
+
+        assert_eq!(Some(procedure[0].clone()), simulator.redo());
+        assert_eq!(Some(procedure[1].clone()), simulator.redo());
+        assert_eq!(Some(procedure[1].clone()), simulator.undo());
+
+        for instruction in procedure.iter().skip(1) {
+            assert_eq!(Some(instruction.clone()), simulator.redo());
+        }
+        assert_eq!(None, simulator.redo());
+        assert_eq!(finished, simulator.get_stacks());
+
+        simulator.undo();
+        simulator.step(&procedure[3]).unwrap();
+        assert_eq!(None, simulator.redo());
+        assert_eq!(4, simulator.get_moves_done());
+    }
+}
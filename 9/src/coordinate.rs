@@ -0,0 +1,84 @@
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    str::FromStr,
+};
+
+// The integers a rope's positions can be measured in. Every sum goes through the checked
+// methods so a rope that runs off the edge of the type is reported rather than wrapping.
+pub trait Coordinate: Copy + Ord + Hash + Debug + Display + FromStr + Into<i64> {
+    const ZERO: Self;
+    const ONE: Self;
+    const MINUS_ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_abs(self) -> Option<Self>;
+    fn signum(self) -> Self;
+}
+
+impl Coordinate for i32 {
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+    const MINUS_ONE: Self = -1;
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        i32::checked_add(self, other)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        i32::checked_sub(self, other)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        i32::checked_mul(self, other)
+    }
+
+    fn checked_abs(self) -> Option<Self> {
+        i32::checked_abs(self)
+    }
+
+    fn signum(self) -> Self {
+        i32::signum(self)
+    }
+}
+
+impl Coordinate for i64 {
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+    const MINUS_ONE: Self = -1;
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        i64::checked_add(self, other)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        i64::checked_sub(self, other)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        i64::checked_mul(self, other)
+    }
+
+    fn checked_abs(self) -> Option<Self> {
+        i64::checked_abs(self)
+    }
+
+    fn signum(self) -> Self {
+        i64::signum(self)
+    }
+}
+
+pub type Position<C> = (C, C);
+
+pub fn add_positions<C: Coordinate>(a: Position<C>, b: Position<C>) -> Option<Position<C>> {
+    Some((a.0.checked_add(b.0)?, a.1.checked_add(b.1)?))
+}
+
+pub fn scale_position<C: Coordinate>(position: Position<C>, factor: C) -> Option<Position<C>> {
+    Some((
+        position.0.checked_mul(factor)?,
+        position.1.checked_mul(factor)?,
+    ))
+}
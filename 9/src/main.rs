@@ -1,22 +1,80 @@
-use std::env;
+mod coordinate;
+mod trail;
+
+use coordinate::{add_positions, scale_position, Coordinate, Position};
+use std::{any, env, fmt::Display, process, str::FromStr};
 use std::{collections::HashSet, fs};
+use trail::{get_grid_area, render_trail, trail_to_csv, Trail};
 
-type Distance = i16;
+#[derive(Debug, PartialEq)]
+enum Motion<C> {
+    Right(C),
+    Up(C),
+    Left(C),
+    Down(C),
+    UpRight(C),
+    UpLeft(C),
+    DownRight(C),
+    DownLeft(C),
+}
 
-type Position = (Distance, Distance);
+impl<C: Coordinate> Motion<C> {
+    // How far the head goes along each axis in one step.
+    fn get_step(&self) -> Position<C> {
+        match self {
+            Motion::Right(_) => (C::ONE, C::ZERO),
+            Motion::Up(_) => (C::ZERO, C::ONE),
+            Motion::Left(_) => (C::MINUS_ONE, C::ZERO),
+            Motion::Down(_) => (C::ZERO, C::MINUS_ONE),
+            Motion::UpRight(_) => (C::ONE, C::ONE),
+            Motion::UpLeft(_) => (C::MINUS_ONE, C::ONE),
+            Motion::DownRight(_) => (C::ONE, C::MINUS_ONE),
+            Motion::DownLeft(_) => (C::MINUS_ONE, C::MINUS_ONE),
+        }
+    }
 
-#[derive(Debug, PartialEq)]
-enum Motion {
-    Right(Distance),
-    Up(Distance),
-    Left(Distance),
-    Down(Distance),
+    fn get_distance(&self) -> C {
+        match self {
+            Motion::Right(distance)
+            | Motion::Up(distance)
+            | Motion::Left(distance)
+            | Motion::Down(distance)
+            | Motion::UpRight(distance)
+            | Motion::UpLeft(distance)
+            | Motion::DownRight(distance)
+            | Motion::DownLeft(distance) => *distance,
+        }
+    }
 }
 
+// `trails` holds where every knot has been, one position per step, starting with where it began.
+// A knot only moves once it's more than `slack` further than touching distance from the knot
+// ahead of it.
 #[derive(Debug, PartialEq)]
-struct State {
-    visited: HashSet<Position>,
-    rope: Vec<Position>,
+struct State<C> {
+    rope: Vec<Position<C>>,
+    slack: C,
+    trails: Vec<Trail<C>>,
+}
+
+// The knot that couldn't take its next step without leaving the coordinate type's range.
+#[derive(Debug, PartialEq, Eq)]
+struct OverflowError<C> {
+    knot: usize,
+    position: Position<C>,
+}
+
+impl<C: Coordinate> Display for OverflowError<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "knot {} can't move on from ({}, {}) without overflowing {}",
+            self.knot,
+            self.position.0,
+            self.position.1,
+            any::type_name::<C>()
+        )
+    }
 }
 
 fn load_input() -> String {
@@ -24,102 +82,275 @@ fn load_input() -> String {
     fs::read_to_string(args.get(1).unwrap()).expect("Should have been able to read the file")
 }
 
-fn parse_motion(input: &str) -> Motion {
-    let mut split = input.split(" ");
-    let (direction, distance) = (split.next().unwrap(), split.next().unwrap());
-    let distance_value = distance.parse::<Distance>().unwrap();
+// The line, counting from one, that isn't a direction and a distance.
+#[derive(Debug, PartialEq, Eq)]
+struct ParseMotionError {
+    line: usize,
+    motion: String,
+}
+
+fn parse_motion<C: Coordinate>(line: usize, input: &str) -> Result<Motion<C>, ParseMotionError> {
+    let error = || ParseMotionError {
+        line,
+        motion: input.to_string(),
+    };
+
+    let (direction, distance) = input.split_once(' ').ok_or_else(error)?;
+    let distance_value = distance.parse::<C>().map_err(|_| error())?;
 
     match direction {
-        "R" => Motion::Right(distance_value),
-        "U" => Motion::Up(distance_value),
-        "L" => Motion::Left(distance_value),
-        "D" => Motion::Down(distance_value),
-        _ => panic!("Unknown Motion"),
+        "R" => Ok(Motion::Right(distance_value)),
+        "U" => Ok(Motion::Up(distance_value)),
+        "L" => Ok(Motion::Left(distance_value)),
+        "D" => Ok(Motion::Down(distance_value)),
+        "UR" => Ok(Motion::UpRight(distance_value)),
+        "UL" => Ok(Motion::UpLeft(distance_value)),
+        "DR" => Ok(Motion::DownRight(distance_value)),
+        "DL" => Ok(Motion::DownLeft(distance_value)),
+        _ => Err(error()),
     }
 }
 
-fn parse_motions(input: &str) -> Vec<Motion> {
+fn parse_motions<C: Coordinate>(input: &str) -> Result<Vec<Motion<C>>, ParseMotionError> {
     input
-        .split("\n")
-        .filter(|a| a != &"")
-        .map(parse_motion)
+        .split('\n')
+        .enumerate()
+        .filter(|(_, a)| a != &"")
+        .map(|(i, motion)| parse_motion(i + 1, motion))
         .collect()
 }
 
-fn get_initial_state(rope_length: usize) -> State {
+fn get_state<C: Coordinate>(rope: Vec<Position<C>>, slack: C) -> State<C> {
     State {
-        visited: HashSet::new(),
-        rope: vec![(0, 0); rope_length],
+        trails: rope.iter().map(|&knot| Trail::new(knot)).collect(),
+        rope,
+        slack,
     }
 }
 
-fn move_tail(tail: Position, head: Position) -> Position {
-    let x_diff = tail.0 - head.0;
-    let y_diff = tail.1 - head.1;
+fn get_initial_state<C: Coordinate>(rope_length: usize, slack: C) -> State<C> {
+    get_state(vec![(C::ZERO, C::ZERO); rope_length], slack)
+}
 
-    let move_x = if x_diff < 0 { 1 } else { -1 };
+fn get_visited<C: Coordinate>(state: &State<C>, knot: usize) -> HashSet<Position<C>> {
+    state.trails[knot].get_positions().into_iter().collect()
+}
 
-    let move_y = if y_diff < 0 { 1 } else { -1 };
+fn move_tail<C: Coordinate>(tail: Position<C>, head: Position<C>, slack: C) -> Option<Position<C>> {
+    let x_diff = head.0.checked_sub(tail.0)?;
+    let y_diff = head.1.checked_sub(tail.1)?;
+    let reach = C::ONE.checked_add(slack)?;
 
-    if x_diff.abs() <= 1 && y_diff.abs() <= 1 {
-        return tail;
+    if x_diff.checked_abs()? <= reach && y_diff.checked_abs()? <= reach {
+        return Some(tail);
     }
 
-    if (x_diff.abs() == 0) && (y_diff.abs() == 2) {
-        return (tail.0, tail.1 + move_y);
+    add_positions(tail, (x_diff.signum(), y_diff.signum()))
+}
+
+// Moves the head a single step and lets the rest of the rope follow. The state is left alone if
+// any knot would overflow.
+fn step_rope<C: Coordinate>(
+    state: &mut State<C>,
+    step: Position<C>,
+) -> Result<(), OverflowError<C>> {
+    let mut rope = Vec::with_capacity(state.rope.len());
+
+    for (knot, &position) in state.rope.iter().enumerate() {
+        let moved = match rope.last() {
+            None => add_positions(position, step),
+            Some(&ahead) => move_tail(position, ahead, state.slack),
+        };
+        rope.push(moved.ok_or(OverflowError { knot, position })?);
     }
-    if (x_diff.abs() == 2) && (y_diff.abs() == 0) {
-        return (tail.0 + move_x, tail.1);
+
+    for (trail, &knot) in state.trails.iter_mut().zip(&rope) {
+        trail.push(knot);
     }
+    state.rope = rope;
 
-    (tail.0 + move_x, tail.1 + move_y)
+    Ok(())
 }
 
-fn move_head<'a>(
-    state: &'a mut State,
-    distance: &Distance,
-    movement: &dyn Fn(Position) -> Position,
-) -> &'a mut State {
-    state.visited.insert((0, 0));
-
-    for _ in 0..*distance {
-        state.rope[0] = movement(state.rope[0]);
-        let mut previous = state.rope[0];
+// How far, up to `limit`, every knot can go along `step` without any of them overflowing, built
+// up a power of two at a time from the largest down, keeping each one that still fits.
+fn get_furthest_jump<C: Coordinate>(rope: &[Position<C>], step: Position<C>, limit: C) -> C {
+    let fits = |distance| {
+        scale_position(step, distance).is_some_and(|offset| {
+            rope.iter()
+                .all(|&knot| add_positions(knot, offset).is_some())
+        })
+    };
+
+    let mut powers = vec![C::ONE];
+    while let Some(power) = powers.last().and_then(|&power| power.checked_add(power)) {
+        if power > limit {
+            break;
+        }
+        powers.push(power);
+    }
 
-        for i in 1..state.rope.len() {
-            state.rope[i] = move_tail(state.rope[i], previous);
+    powers.into_iter().rev().fold(C::ZERO, |distance, power| {
+        match distance.checked_add(power) {
+            Some(further) if further <= limit && fits(further) => further,
+            _ => distance,
+        }
+    })
+}
 
-            if i == state.rope.len() - 1 {
-                state.visited.insert(state.rope[i].clone());
+// Steps the head until a step moves every knot by exactly `step`. The rope then has the same
+// shape as before, so every later step does the same and the rest of the distance is one jump.
+// A jump costs the same whatever the distance, as each knot's trail gets one segment for it. It
+// stops short where a knot would overflow, leaving the next step to report where.
+fn run_motion<'a, C: Coordinate>(
+    state: &'a mut State<C>,
+    motion: &Motion<C>,
+) -> Result<&'a mut State<C>, OverflowError<C>> {
+    let step = motion.get_step();
+    let mut remaining = motion.get_distance();
+
+    while remaining > C::ZERO {
+        let before = state.rope.clone();
+        step_rope(state, step)?;
+        remaining = remaining.checked_sub(C::ONE).unwrap();
+
+        let keeps_shape = before
+            .iter()
+            .zip(&state.rope)
+            .all(|(&was, &is)| add_positions(was, step) == Some(is));
+
+        if keeps_shape {
+            let distance = get_furthest_jump(&state.rope, step, remaining);
+            if distance > C::ZERO {
+                let offset = scale_position(step, distance).unwrap();
+                for (trail, knot) in state.trails.iter_mut().zip(state.rope.iter_mut()) {
+                    *knot = add_positions(*knot, offset).unwrap();
+                    trail.extend(step, distance, *knot);
+                }
+                remaining = remaining.checked_sub(distance).unwrap();
             }
+        }
+    }
 
-            previous = state.rope[i];
+    Ok(state)
+}
+
+// Runs every motion, stopping the program with the offending line if the rope overflows.
+fn run_motions<'a, C: Coordinate>(
+    state: &'a mut State<C>,
+    motions: &[Motion<C>],
+) -> &'a mut State<C> {
+    for (line, motion) in motions.iter().enumerate() {
+        if let Err(error) = run_motion(state, motion) {
+            eprintln!("Motion on line {}: {}", line + 1, error);
+            process::exit(1);
         }
     }
 
     state
 }
 
-fn run_motion<'a>(state: &'a mut State, motion: &Motion) -> &'a mut State {
-    match motion {
-        Motion::Right(dist) => move_head(state, dist, &|(x, y)| (x + 1, y)),
-        Motion::Up(dist) => move_head(state, dist, &|(x, y)| (x, y + 1)),
-        Motion::Left(dist) => move_head(state, dist, &|(x, y)| (x - 1, y)),
-        Motion::Down(dist) => move_head(state, dist, &|(x, y)| (x, y - 1)),
+// Trails are only written out a position at a time up to this many steps.
+const MAX_EXPORTED_POSITIONS: usize = 10_000_000;
+// And only drawn on a grid up to this many cells.
+const MAX_RENDERED_CELLS: u128 = 10_000_000;
+
+fn load_output_format() -> Option<String> {
+    env::args().nth(2)
+}
+
+// The rope length, slack and knot to export, each optional: `10 0 9` by default.
+fn load_trail_options() -> (usize, i64, usize) {
+    let args: Vec<String> = env::args().skip(3).collect();
+    fn number<T: FromStr>(name: &str, value: &str) -> T {
+        value.parse().unwrap_or_else(|_| {
+            eprintln!("The {} should be a number, got {}", name, value);
+            process::exit(1);
+        })
+    }
+
+    let rope_length = args
+        .first()
+        .map_or(10, |length| number("rope length", length));
+    let slack = args.get(1).map_or(0, |slack| number("slack", slack));
+
+    if rope_length == 0 {
+        eprintln!("A rope needs at least one knot");
+        process::exit(1);
     }
+
+    if slack < 0 {
+        eprintln!("The slack can't be negative, got {}", slack);
+        process::exit(1);
+    }
+
+    let knot = args
+        .get(2)
+        .map_or(rope_length - 1, |knot| number("knot", knot));
+
+    if knot >= rope_length {
+        eprintln!("Knot {} is not on a rope of {} knots", knot, rope_length);
+        process::exit(1);
+    }
+
+    (rope_length, slack, knot)
 }
 
 fn main() {
     let input = load_input();
-    let motions = parse_motions(&input);
-    let mut initial_state = get_initial_state(2);
-    let final_state = motions.iter().fold(&mut initial_state, run_motion);
-    let number_tail_visited = final_state.visited.len();
+    let motions: Vec<Motion<i64>> = parse_motions(&input).unwrap_or_else(|error| {
+        eprintln!("Line {} is not a motion: {:?}", error.line, error.motion);
+        process::exit(1);
+    });
+
+    if let Some(format) = load_output_format() {
+        let (rope_length, slack, knot) = load_trail_options();
+        let mut initial_state = get_initial_state(rope_length, slack);
+        let final_state = run_motions(&mut initial_state, &motions);
+        let trail = &final_state.trails[knot];
+
+        match trail.get_length() {
+            Some(length) if length <= MAX_EXPORTED_POSITIONS => {}
+            Some(length) => {
+                eprintln!(
+                    "Knot {}'s trail has {} positions, too many to export",
+                    knot, length
+                );
+                process::exit(1);
+            }
+            None => {
+                eprintln!("Knot {}'s trail has too many positions to count", knot);
+                process::exit(1);
+            }
+        }
+
+        let positions = trail.get_positions();
+
+        match format.as_str() {
+            "--grid" if get_grid_area(&positions) > MAX_RENDERED_CELLS => {
+                eprintln!(
+                    "Knot {}'s trail covers {} cells, too many to draw",
+                    knot,
+                    get_grid_area(&positions)
+                );
+                process::exit(1);
+            }
+            "--grid" => println!("{}", render_trail(&positions)),
+            "--csv" => print!("{}", trail_to_csv(&positions)),
+            _ => panic!("Unknown output format {}", format),
+        }
+
+        return;
+    }
+
+    let mut initial_state = get_initial_state(2, 0);
+    let final_state = run_motions(&mut initial_state, &motions);
+    let number_tail_visited = get_visited(final_state, 1).len();
     println!("{}", number_tail_visited);
 
-    let mut initial_state_2 = get_initial_state(10);
-    let final_state_2 = motions.iter().fold(&mut initial_state_2, run_motion);
-    let number_tail_visited_2 = final_state_2.visited.len();
+    let mut initial_state_2 = get_initial_state(10, 0);
+    let final_state_2 = run_motions(&mut initial_state_2, &motions);
+    let number_tail_visited_2 = get_visited(final_state_2, 9).len();
     println!("{}", number_tail_visited_2);
 }
 
@@ -127,11 +358,29 @@ fn main() {
 mod tests {
     use std::collections::HashSet;
 
-    use crate::{parse_motions, run_motion, Motion, State};
+    use crate::{
+        coordinate::Coordinate, get_initial_state, get_state, get_visited, parse_motions,
+        run_motion, step_rope, Motion, OverflowError, ParseMotionError, State,
+    };
+
+    // The head a step at a time, as the rope moved before taut ropes could jump.
+    fn run_motion_by_steps<'a, C: Coordinate>(
+        state: &'a mut State<C>,
+        motion: &Motion<C>,
+    ) -> Result<&'a mut State<C>, OverflowError<C>> {
+        let mut remaining = motion.get_distance();
+
+        while remaining > C::ZERO {
+            step_rope(state, motion.get_step())?;
+            remaining = remaining.checked_sub(C::ONE).unwrap();
+        }
+
+        Ok(state)
+    }
 
     #[test]
     fn test_parse_motions() {
-        let input = "R 4\nU 4\nL 3\nD 1\nR 4\nD 1\nL 5\nR 2\n";
+        let input = "R 4\nU 4\nL 3\nD 1\nR 4\nD 1\nL 5\nR 2\nUR 2\nDL 3\n";
         let expected = vec![
             Motion::Right(4),
             Motion::Up(4),
@@ -141,65 +390,257 @@ mod tests {
             Motion::Down(1),
             Motion::Left(5),
             Motion::Right(2),
+            Motion::UpRight(2),
+            Motion::DownLeft(3),
         ];
-        let actual = parse_motions(&input);
+        let actual = parse_motions(input).unwrap();
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_parse_motion_errors() {
+        for motion in ["R", "R x", "X 3", "R 3 4", "UR -"] {
+            assert_eq!(
+                Err(ParseMotionError {
+                    line: 2,
+                    motion: motion.to_string()
+                }),
+                parse_motions::<i32>(&format!("R 1\n{}\nL 1", motion))
+            );
+        }
+        assert_eq!(
+            Err(ParseMotionError {
+                line: 1,
+                motion: "R 3000000000".to_string()
+            }),
+            parse_motions::<i32>("R 3000000000")
+        );
+        assert_eq!(
+            Ok(vec![Motion::Right(3_000_000_000i64)]),
+            parse_motions("R 3000000000\n")
+        );
+    }
+
     #[test]
     fn test_direct_motions() {
-        let mut input_state_right = State {
-            visited: HashSet::new(),
-            rope: vec![(1, 0), (0, 0)],
-        };
+        let mut input_state_right = get_state(vec![(1, 0), (0, 0)], 0);
         let input_motion_right = Motion::Right(1);
-        let expected_state_right = State {
-            visited: HashSet::from([(0, 0), (1, 0)]),
-            rope: vec![(2, 0), (1, 0)],
-        };
-        let actual_state_right = run_motion(&mut input_state_right, &input_motion_right);
-        assert_eq!(&expected_state_right, actual_state_right);
-
-        let mut input_state_left = State {
-            visited: HashSet::new(),
-            rope: vec![(0, -1), (0, 0)],
-        };
+        let actual_state_right = run_motion(&mut input_state_right, &input_motion_right).unwrap();
+        assert_eq!(vec![(2, 0), (1, 0)], actual_state_right.rope);
+        assert_eq!(
+            HashSet::from([(0, 0), (1, 0)]),
+            get_visited(actual_state_right, 1)
+        );
+
+        let mut input_state_left = get_state(vec![(0, -1), (0, 0)], 0);
         let input_motion_left = Motion::Down(1);
-        let expected_state_left = State {
-            visited: HashSet::from([(0, 0), (0, -1)]),
-            rope: vec![(0, -2), (0, -1)],
-        };
-
-        let actual_state_left = run_motion(&mut input_state_left, &input_motion_left);
-        assert_eq!(&expected_state_left, actual_state_left);
+        let actual_state_left = run_motion(&mut input_state_left, &input_motion_left).unwrap();
+        assert_eq!(vec![(0, -2), (0, -1)], actual_state_left.rope);
+        assert_eq!(
+            HashSet::from([(0, 0), (0, -1)]),
+            get_visited(actual_state_left, 1)
+        );
     }
 
     #[test]
     fn test_diagonal_motions() {
-        let mut input_state_diag_up = State {
-            visited: HashSet::new(),
-            rope: vec![(1, 1), (0, 0)],
-        };
+        let mut input_state_diag_up = get_state(vec![(1, 1), (0, 0)], 0);
         let input_motion_diag_up = Motion::Up(1);
-        let expected_state_diag_up = State {
-            visited: HashSet::from([(0, 0), (1, 1)]),
-            rope: vec![(1, 2), (1, 1)],
-        };
-
-        let actual_state_diag_up = run_motion(&mut input_state_diag_up, &input_motion_diag_up);
-        assert_eq!(&expected_state_diag_up, actual_state_diag_up);
-
-        let mut input_state_diag_right = State {
-            visited: HashSet::new(),
-            rope: vec![(1, 1), (0, 0)],
-        };
+        let actual_state_diag_up =
+            run_motion(&mut input_state_diag_up, &input_motion_diag_up).unwrap();
+        assert_eq!(vec![(1, 2), (1, 1)], actual_state_diag_up.rope);
+        assert_eq!(
+            HashSet::from([(0, 0), (1, 1)]),
+            get_visited(actual_state_diag_up, 1)
+        );
+
+        let mut input_state_diag_right = get_state(vec![(1, 1), (0, 0)], 0);
         let input_motion_diag_right = Motion::Right(1);
-        let expected_state_diag_right = State {
-            visited: HashSet::from([(0, 0), (1, 1)]),
-            rope: vec![(2, 1), (1, 1)],
-        };
         let actual_state_diag_right =
-            run_motion(&mut input_state_diag_right, &input_motion_diag_right);
-        assert_eq!(&expected_state_diag_right, actual_state_diag_right);
+            run_motion(&mut input_state_diag_right, &input_motion_diag_right).unwrap();
+        assert_eq!(vec![(2, 1), (1, 1)], actual_state_diag_right.rope);
+
+        let mut input_state_up_right = get_state(vec![(0, 0), (0, 0), (0, 0)], 0);
+        let actual_state_up_right =
+            run_motion(&mut input_state_up_right, &Motion::UpRight(3)).unwrap();
+        assert_eq!(vec![(3, 3), (2, 2), (1, 1)], actual_state_up_right.rope);
+
+        let actual_state_down_left =
+            run_motion(actual_state_up_right, &Motion::DownLeft(1)).unwrap();
+        assert_eq!(vec![(2, 2), (2, 2), (1, 1)], actual_state_down_left.rope);
+    }
+
+    #[test]
+    fn test_every_knot() {
+        let motions = parse_motions("R 5\nU 8\nL 8\nD 3\nR 17\nD 10\nL 25\nU 20").unwrap();
+        let mut initial_state = get_initial_state(10, 0);
+        let final_state = motions
+            .iter()
+            .try_fold(&mut initial_state, run_motion)
+            .unwrap();
+
+        assert_eq!(36, get_visited(final_state, 9).len());
+        for knot in 0..10 {
+            assert_eq!(Some(1 + 96), final_state.trails[knot].get_length());
+        }
+
+        let mut initial_state_2 = get_initial_state(2, 0);
+        let final_state_2 = motions
+            .iter()
+            .try_fold(&mut initial_state_2, run_motion)
+            .unwrap();
+        assert_eq!(get_visited(final_state, 1), get_visited(final_state_2, 1));
+    }
+
+    #[test]
+    fn test_slack() {
+        let mut state = get_initial_state(2, 2);
+        let state = run_motion(&mut state, &Motion::Right(3)).unwrap();
+        assert_eq!(vec![(3, 0), (0, 0)], state.rope);
+
+        let state = run_motion(state, &Motion::Right(2)).unwrap();
+        assert_eq!(vec![(5, 0), (2, 0)], state.rope);
+
+        let state = run_motion(state, &Motion::Up(4)).unwrap();
+        assert_eq!(vec![(5, 4), (3, 1)], state.rope);
+        assert_eq!(
+            HashSet::from([(0, 0), (1, 0), (2, 0), (3, 1)]),
+            get_visited(state, 1)
+        );
+    }
+
+    #[test]
+    fn test_jumps_match_steps() {
+        let directions = ["R", "U", "L", "D", "UR", "UL", "DR", "DL"];
+
+        // Every direction followed by every other, so the rope starts each motion bent a
+        // different way, and goes far enough to straighten out and jump.
+        let input: Vec<String> = (0..64)
+            .flat_map(|i| {
+                [
+                    format!("{} {}", directions[i % 8], i % 5),
+                    format!("{} {}", directions[i / 8], 20 + i * 7),
+                ]
+            })
+            .collect();
+        let motions: Vec<Motion<i64>> = parse_motions(&input.join("\n")).unwrap();
+
+        for rope_length in 1..=10 {
+            for slack in 0..=2 {
+                let mut jumping = get_initial_state(rope_length, slack);
+                let mut stepping = get_initial_state(rope_length, slack);
+                for motion in &motions {
+                    run_motion(&mut jumping, motion).unwrap();
+                    run_motion_by_steps(&mut stepping, motion).unwrap();
+                    assert_eq!(stepping, jumping, "{:?}", motion);
+                }
+            }
+        }
+    }
+
+    // The answers the original step-by-step simulator gave for these motions.
+    #[test]
+    fn test_puzzle_examples() {
+        let expected = [
+            (include_str!("../test.txt"), 13, 1),
+            (include_str!("../test_2.txt"), 88, 36),
+            (include_str!("../test_3.txt"), 2095, 1234),
+        ];
+
+        for (input, two_knots, ten_knots) in expected {
+            let motions: Vec<Motion<i32>> = parse_motions(input).unwrap();
+
+            let mut initial_state = get_initial_state(2, 0);
+            let final_state = motions
+                .iter()
+                .try_fold(&mut initial_state, run_motion)
+                .unwrap();
+            assert_eq!(two_knots, get_visited(final_state, 1).len());
+
+            let mut initial_state_2 = get_initial_state(10, 0);
+            let final_state_2 = motions
+                .iter()
+                .try_fold(&mut initial_state_2, run_motion)
+                .unwrap();
+            assert_eq!(ten_knots, get_visited(final_state_2, 9).len());
+        }
+    }
+
+    #[test]
+    fn test_long_jump() {
+        let distance = 1i64 << 60;
+        let mut state = get_initial_state(10, 0);
+        let state = run_motion(&mut state, &Motion::Right(distance)).unwrap();
+
+        assert_eq!((distance, 0), state.rope[0]);
+        assert_eq!((distance - 9, 0), state.rope[9]);
+        assert_eq!(Some(distance as usize + 1), state.trails[0].get_length());
+        assert_eq!((distance - 9, 0), state.trails[9].last());
+
+        // Diagonally, with the tail still off to one side of the head.
+        let mut state = get_initial_state(2, 0);
+        run_motion(&mut state, &Motion::Right(1)).unwrap();
+        let state = run_motion(&mut state, &Motion::UpRight(distance)).unwrap();
+
+        assert_eq!((distance + 1, distance), state.rope[0]);
+        assert_eq!((distance, distance), state.rope[1]);
+        assert_eq!(Some(distance as usize + 2), state.trails[0].get_length());
+
+        // Up to the edge in one jump, then the step over it is reported.
+        let mut state = get_state(vec![(1, 0), (0, 0)], 0);
+        assert_eq!(
+            Err(OverflowError {
+                knot: 0,
+                position: (i64::MAX, 0)
+            }),
+            run_motion(&mut state, &Motion::Right(i64::MAX)).map(|_| ())
+        );
+        assert_eq!(vec![(i64::MAX, 0), (i64::MAX - 1, 0)], state.rope);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let edge = i32::MAX - 2;
+        let mut state = get_state(vec![(edge, 0), (edge - 1, 0)], 0);
+        assert_eq!(
+            Err(OverflowError {
+                knot: 0,
+                position: (i32::MAX, 0)
+            }),
+            run_motion(&mut state, &Motion::Right(5)).map(|_| ())
+        );
+        assert_eq!(vec![(i32::MAX, 0), (i32::MAX - 1, 0)], state.rope);
+        assert_eq!(
+            "knot 0 can't move on from (2147483647, 0) without overflowing i32",
+            run_motion(&mut state, &Motion::UpRight(1))
+                .unwrap_err()
+                .to_string()
+        );
+
+        let mut state = get_state(vec![(i64::MIN + 1, 5)], 0);
+        assert_eq!(
+            Err(OverflowError {
+                knot: 0,
+                position: (i64::MIN, 4)
+            }),
+            run_motion(&mut state, &Motion::DownLeft(3)).map(|_| ())
+        );
+
+        let mut state = get_state(vec![(i64::MAX, 0), (-2, 0)], 1);
+        assert_eq!(
+            Err(OverflowError {
+                knot: 1,
+                position: (-2, 0)
+            }),
+            run_motion(&mut state, &Motion::Left(1)).map(|_| ())
+        );
+        assert_eq!(vec![(i64::MAX, 0), (-2, 0)], state.rope);
+
+        let mut wide = get_state(vec![(i32::MAX as i64, 0); 2], 0);
+        run_motion(&mut wide, &Motion::Right(5)).unwrap();
+        assert_eq!(
+            vec![(i32::MAX as i64 + 5, 0), (i32::MAX as i64 + 4, 0)],
+            wide.rope
+        );
     }
 }
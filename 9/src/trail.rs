@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+
+use crate::coordinate::{add_positions, Coordinate, Position};
+
+// A run of equal steps, so a knot that goes a long way in a straight line costs one entry.
+#[derive(Debug, PartialEq)]
+struct Segment<C> {
+    step: Position<C>,
+    count: C,
+    end: Position<C>,
+}
+
+// Where a knot has been, one position per step, starting with where it began.
+#[derive(Debug, PartialEq)]
+pub struct Trail<C> {
+    start: Position<C>,
+    segments: Vec<Segment<C>>,
+}
+
+impl<C: Coordinate> Trail<C> {
+    pub fn new(start: Position<C>) -> Trail<C> {
+        Trail {
+            start,
+            segments: Vec::new(),
+        }
+    }
+
+    pub fn last(&self) -> Position<C> {
+        self.segments
+            .last()
+            .map_or(self.start, |segment| segment.end)
+    }
+
+    // Records `count` steps of `step`, finishing at `end`, which the caller has already found
+    // without overflowing.
+    pub fn extend(&mut self, step: Position<C>, count: C, end: Position<C>) {
+        if let Some(segment) = self.segments.last_mut() {
+            if let (true, Some(total)) = (segment.step == step, segment.count.checked_add(count)) {
+                segment.count = total;
+                segment.end = end;
+                return;
+            }
+        }
+
+        self.segments.push(Segment { step, count, end });
+    }
+
+    // Records a single step to a position next to the last one.
+    pub fn push(&mut self, position: Position<C>) {
+        let last = self.last();
+        let step = (
+            position.0.checked_sub(last.0).unwrap(),
+            position.1.checked_sub(last.1).unwrap(),
+        );
+
+        self.extend(step, C::ONE, position);
+    }
+
+    // How many positions `get_positions` would give, counting the start, or `None` if that's
+    // more than a `usize` can count.
+    pub fn get_length(&self) -> Option<usize> {
+        self.segments.iter().try_fold(1usize, |length, segment| {
+            let count: i64 = segment.count.into();
+            length.checked_add(usize::try_from(count).ok()?)
+        })
+    }
+
+    pub fn get_positions(&self) -> Vec<Position<C>> {
+        let mut positions = vec![self.start];
+
+        for segment in &self.segments {
+            let mut position = *positions.last().unwrap();
+
+            for _ in 0..segment.count.into() {
+                position = add_positions(position, segment.step).unwrap();
+                positions.push(position);
+            }
+        }
+
+        positions
+    }
+}
+
+// The smallest and largest x and y the trail reaches.
+fn get_bounds(trail: &[Position<i64>]) -> (i64, i64, i64, i64) {
+    let x_min = trail.iter().map(|position| position.0).min().unwrap_or(0);
+    let x_max = trail.iter().map(|position| position.0).max().unwrap_or(0);
+    let y_min = trail.iter().map(|position| position.1).min().unwrap_or(0);
+    let y_max = trail.iter().map(|position| position.1).max().unwrap_or(0);
+
+    (x_min, x_max, y_min, y_max)
+}
+
+fn to_i64<C: Coordinate>(trail: &[Position<C>]) -> Vec<Position<i64>> {
+    trail.iter().map(|&(x, y)| (x.into(), y.into())).collect()
+}
+
+// How many cells `render_trail` would draw, saturating for grids too large to count.
+pub fn get_grid_area<C: Coordinate>(trail: &[Position<C>]) -> u128 {
+    let (x_min, x_max, y_min, y_max) = get_bounds(&to_i64(trail));
+    let width = (x_max as i128 - x_min as i128 + 1) as u128;
+    let height = (y_max as i128 - y_min as i128 + 1) as u128;
+
+    width.saturating_mul(height)
+}
+
+// The trail on a grid with up at the top: `s` where it started, `#` everywhere else it went.
+pub fn render_trail<C: Coordinate>(trail: &[Position<C>]) -> String {
+    let trail = to_i64(trail);
+    let (x_min, x_max, y_min, y_max) = get_bounds(&trail);
+
+    let visited: HashSet<Position<i64>> = trail.iter().copied().collect();
+    let start = trail.first().copied();
+
+    (y_min..=y_max)
+        .rev()
+        .map(|y| {
+            (x_min..=x_max)
+                .map(
+                    |x| match (Some((x, y)) == start, visited.contains(&(x, y))) {
+                        (true, _) => 's',
+                        (false, true) => '#',
+                        (false, false) => '.',
+                    },
+                )
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// One line per step, the starting position being step 0.
+pub fn trail_to_csv<C: Coordinate>(trail: &[Position<C>]) -> String {
+    trail
+        .iter()
+        .enumerate()
+        .fold("step,x,y\n".to_string(), |csv, (step, (x, y))| {
+            csv + &format!("{},{},{}\n", step, x, y)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_grid_area, render_trail, trail_to_csv, Trail};
+
+    #[test]
+    fn test_segments() {
+        let mut trail = Trail::new((0i64, 0));
+        trail.push((1, 0));
+        trail.push((2, 0));
+        trail.extend((1, 0), 3, (5, 0));
+        trail.push((5, 0));
+        trail.push((6, 1));
+
+        assert_eq!(3, trail.segments.len());
+        assert_eq!(Some(8), trail.get_length());
+        assert_eq!((6, 1), trail.last());
+        assert_eq!(
+            vec![
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (3, 0),
+                (4, 0),
+                (5, 0),
+                (5, 0),
+                (6, 1)
+            ],
+            trail.get_positions()
+        );
+
+        let mut long = Trail::new((0i64, 0));
+        long.extend((0, -1), i64::MAX, (0, -i64::MAX));
+        long.push((0, i64::MIN));
+        assert_eq!(2, long.segments.len());
+        assert_eq!((0, i64::MIN), long.last());
+
+        let mut there_and_back = Trail::new((0i64, 0));
+        there_and_back.extend((1, 0), i64::MAX - 1, (i64::MAX - 1, 0));
+        there_and_back.extend((-1, 0), i64::MAX - 1, (0, 0));
+        assert_eq!(
+            Some(2 * (i64::MAX as usize - 1) + 1),
+            there_and_back.get_length()
+        );
+        there_and_back.extend((1, 0), i64::MAX - 1, (i64::MAX - 1, 0));
+        assert_eq!(None, there_and_back.get_length());
+    }
+
+    #[test]
+    fn test_render_trail() {
+        let trail: Vec<(i32, i32)> = vec![(0, 0), (1, 0), (2, 1), (2, 2), (1, 2)];
+        assert_eq!(".##\n..#\ns#.", render_trail(&trail));
+        assert_eq!("s", render_trail(&[(4i64, -3)]));
+        assert_eq!("s#", render_trail(&[(0, 0), (1, 0), (0, 0)]));
+
+        assert_eq!(9, get_grid_area(&trail));
+        assert_eq!(
+            25_000_000_000_000,
+            get_grid_area(&[(0, 0), (4_999_999i64, 4_999_999)])
+        );
+        assert_eq!(
+            u128::MAX,
+            get_grid_area(&[(i64::MIN, i64::MIN), (i64::MAX, i64::MAX)])
+        );
+    }
+
+    #[test]
+    fn test_trail_to_csv() {
+        let trail: Vec<(i64, i64)> = vec![(0, 0), (-1, 0), (-1, 1)];
+        assert_eq!("step,x,y\n0,0,0\n1,-1,0\n2,-1,1\n", trail_to_csv(&trail));
+    }
+}